@@ -1,5 +1,21 @@
+use crate::model::Zoom;
+use crate::styles::text::fonts;
+
+
+
+/// Size of the cell labels, which are measured in `styles::metrics`.
+///
+pub const TEXT_SIZE: u16 = crate::styles::metrics::LABEL_SIZE;
+
+
 pub trait SimpleView {
     fn view(&self) -> (u16, iced::Element<'static, ()>);
+
+    /// Like `view`, drawn at `zoom`. The width stays at the natural scale.
+    ///
+    fn view_zoomed(&self, _zoom: Zoom) -> (u16, iced::Element<'static, ()>) {
+        self.view()
+    }
 }
 
 pub trait View {
@@ -19,7 +35,13 @@ impl<S: ToString> SimpleView for S {
     fn view(&self) -> (u16, iced::Element<'static, ()>) {
         let s = self.to_string();
 
-        (21 * s.len() as u16 / 2, iced::Text::new(s).into())// TODO: account for different char widths
+        (crate::utils::text_width(&s), iced::Text::new(s).font(fonts::LABEL).into())
+    }
+
+    fn view_zoomed(&self, zoom: Zoom) -> (u16, iced::Element<'static, ()>) {
+        let s = self.to_string();
+
+        (crate::utils::text_width(&s), iced::Text::new(s).font(fonts::LABEL).size(zoom.scale(TEXT_SIZE)).into())
     }
 }
 
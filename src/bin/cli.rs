@@ -0,0 +1,283 @@
+use std::fmt;
+use std::{ env, fs, io::{ self, Write }, process };
+use std::path::{ Path, PathBuf };
+
+use eru::export;
+use eru::opetope::{ self, format, journal::{ self, Journal }, script::{ self, Script }, validation::Violation, Diagram, Tail };
+
+
+
+const USAGE: &str = "\
+Usage: eru-cli <command> <file> [<output>] [--svg | --tikz]
+
+Commands:
+    show <file>                 print the levels and the cell tree
+    validate <file>             check the opetope invariants
+    convert <file> <output>     rewrite in the current file format
+    render <file> <output>      draw as SVG (`.svg`) or TikZ (`.tex`)
+    journal <file>              print the edits which built the opetope, as a script
+    run <file> <script> <output>
+                                apply the edit script and save the result
+
+An output of `-` means the standard output, the picture format is then chosen by `--svg` (default) or `--tikz`.";
+
+type Data = String;
+
+
+enum Error {
+    Usage(String),
+
+    File(PathBuf, io::Error),
+    Format(PathBuf, format::Error),
+    Invalid(PathBuf, Vec<Violation>),
+    Script(PathBuf, script::Error),
+    Journal(PathBuf, journal::Error),
+
+    Opetope(opetope::Error),
+    Export(export::Error),
+}
+impl From<opetope::Error> for Error {
+    fn from(e: opetope::Error) -> Self {
+        Self::Opetope(e)
+    }
+}
+impl From<export::Error> for Error {
+    fn from(e: export::Error) -> Self {
+        Self::Export(e)
+    }
+}
+
+
+
+fn main() {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+
+    let mut picture = None;
+
+    args.retain(|arg|
+        match arg.as_str() {
+            "--svg" => { picture = Some(export::Format::Svg); false },
+            "--tikz" => { picture = Some(export::Format::Tikz); false },
+
+            _ => true,
+        }
+    );
+
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+
+    let result =
+    match &args[..] {
+        ["show", file] =>
+            load(file).and_then(|diagram| show(&Tail::Diagram(Box::new(diagram)), &mut io::stdout().lock())),
+        ["validate", file] => validate(file),
+        ["convert", file, output] => convert(file, output),
+        ["render", file, output] => render(file, output, picture),
+        ["journal", file] => print_journal(file),
+        ["run", file, script, output] => run(file, script, output),
+
+        ["help"] | ["--help"] | ["-h"] =>
+            writeln![io::stdout().lock(), "{}", USAGE].map_err(stdout_error),
+
+        _ => Err(Error::Usage(args.join(" "))),
+    };
+
+    if let Err(e) = result {
+        // NOTE: The reader of the output, e.g. `head`, may have stopped early.
+        //
+        if matches![&e, Error::File(_, e) if e.kind() == io::ErrorKind::BrokenPipe] {
+            return;
+        }
+
+        eprintln!["{}", e];
+
+        process::exit(
+            match e {
+                Error::Usage(_) => 2,
+                _ => 1,
+            }
+        );
+    }
+}
+
+
+fn read(file: &str) -> Result<String, Error> {
+    fs::read_to_string(file)
+        .map_err(|e| Error::File(file.into(), e))
+}
+
+fn load(file: &str) -> Result<Diagram<Data>, Error> {
+    format::from_str(&read(file)?)
+        .map_err(|e| Error::Format(file.into(), e))
+}
+
+fn load_with_journal(file: &str) -> Result<(Diagram<Data>, Journal), Error> {
+    format::from_str_with_journal(&read(file)?)
+        .map_err(|e| Error::Format(file.into(), e))
+}
+
+/// Reports a failed write to the standard output, which is named `-` as in the usage.
+///
+fn stdout_error(e: io::Error) -> Error {
+    Error::File("-".into(), e)
+}
+
+/// Writes to the file, or to the standard output if it is `-`.
+///
+fn store(output: &str, contents: &str) -> Result<(), Error> {
+    if output == "-" {
+        writeln![io::stdout().lock(), "{}", contents]
+            .map_err(stdout_error)
+
+    } else {
+        fs::write(output, contents)
+            .map_err(|e| Error::File(output.into(), e))
+    }
+}
+
+
+fn show(tail: &Tail<Data>, out: &mut impl Write) -> Result<(), Error> {
+    if let Tail::Diagram(diagram) = tail {
+        show(diagram.prev(), out)?;
+    }
+
+    writeln![out, "level {}", tail.level()].map_err(stdout_error)?;
+
+    for index in tail.indices() {
+        let cell = tail.cell(&index)?;
+
+        let indent = "  ".repeat(index.path().len());
+
+        if let Some(face) = cell.face() {
+            let ends =
+            face.ends
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln![out, "{}{} {} : {} → {}", indent, index, cell.data(), ends, face.fill]
+                .map_err(stdout_error)?;
+
+        } else {
+            writeln![out, "{}{} {}", indent, index, cell.data()]
+                .map_err(stdout_error)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(file: &str) -> Result<(), Error> {
+    let diagram = load(file)?;
+
+    diagram
+        .validate()
+        .map_err(|violations| Error::Invalid(file.into(), violations))?;
+
+    writeln![io::stdout().lock(), "{}: ok", file]
+        .map_err(stdout_error)
+}
+
+fn convert(file: &str, output: &str) -> Result<(), Error> {
+    let (diagram, journal) = load_with_journal(file)?;
+
+    let contents =
+    format::to_string_with_journal(&diagram, &journal)
+        .map_err(|e| Error::Format(output.into(), e))?;
+
+    store(output, &contents)
+}
+
+fn render(file: &str, output: &str, picture: Option<export::Format>) -> Result<(), Error> {
+    let diagram = load(file)?;
+
+    diagram
+        .validate()
+        .map_err(|violations| Error::Invalid(file.into(), violations))?;
+
+    let picture =
+    match picture {
+        Some(picture) => picture,
+
+        None if output == "-" => export::Format::Svg,
+        None => export::Format::from_path(Path::new(output))?,
+    };
+
+    store(output, &export::export(&diagram, picture, &export::Palette::default()))
+}
+
+fn print_journal(file: &str) -> Result<(), Error> {
+    let (_, journal) = load_with_journal(file)?;
+
+    // NOTE: Checks that the entries still apply, e.g. after the file was edited by hand.
+    //
+    journal
+        .replay::<Data>()
+        .map_err(|e| Error::Journal(file.into(), e))?;
+
+    let mut out = io::stdout().lock();
+
+    for entry in journal.entries() {
+        writeln![out, "{}", entry.command].map_err(stdout_error)?;
+    }
+
+    Ok(())
+}
+
+fn run(file: &str, script: &str, output: &str) -> Result<(), Error> {
+    let (mut diagram, mut journal) = load_with_journal(file)?;
+
+    let entries =
+    Script::parse(&read(script)?)
+        .and_then(|parsed| parsed.run(&mut diagram))
+        .map_err(|e| Error::Script(script.into(), e))?;
+
+    journal.record(entries);
+
+    let contents =
+    format::to_string_with_journal(&diagram, &journal)
+        .map_err(|e| Error::Format(output.into(), e))?;
+
+    store(output, &contents)
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(args) if args.is_empty() =>
+                write![fmt, "{}", USAGE],
+
+            Self::Usage(args) =>
+                write![fmt, "Unexpected arguments `{}`\n\n{}", args, USAGE],
+
+            Self::File(path, e) =>
+                write![fmt, "{}: {}", path.display(), e],
+
+            Self::Format(path, e) =>
+                write![fmt, "{}: {}", path.display(), e],
+
+            Self::Invalid(path, violations) => {
+                write![fmt, "{} is not a valid opetope:", path.display()]?;
+
+                for violation in violations {
+                    write![fmt, "\n- {}", violation]?;
+                }
+
+                Ok(())
+            },
+
+            Self::Script(path, e) =>
+                write![fmt, "{}: {}", path.display(), e],
+
+            Self::Journal(path, e) =>
+                write![fmt, "{}: {}", path.display(), e],
+
+            Self::Opetope(e) =>
+                write![fmt, "{}", e],
+
+            Self::Export(e) =>
+                write![fmt, "{}", e],
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::{ Path, PathBuf };
 
 
 use iced::{
@@ -6,17 +7,35 @@ use iced::{
     Application,
 };
 
+use eru::model::{ History, Drawing };
+use eru::export;
+use eru::opetope::{
+    self,
+    format::{ self, Named },
+    journal::{ self, Entry, Journal },
+    script::{ self, Command as Edit, Script },
+    validation::Violation,
+    Diagram,
+};
+
 use crate::components::{
-    opetope::{ self, Diagram },
+    workspace::{ self, Workspace },
+    keys::{ self, Keys },
 
     general::{
         sidebar,
+        replay::{ self, Replay },
+        viewport,
+        level_bar,
         main_layout::{ self, State, Layout },
     },
 };
 
 
 
+const SHOWN_VIOLATIONS: usize = 8;
+
+
 pub type Data = opetope::data::Selectable<String>;
 fn to_data(s: impl ToString) -> opetope::data::Selectable<String> {
     let s = s.to_string();
@@ -29,11 +48,23 @@ fn to_data(s: impl ToString) -> opetope::data::Selectable<String> {
     }
 }
 
+/// References to the cells, as they are written in the journal.
+///
+fn names(cells: &[opetope::ViewIndex]) -> Vec<String> {
+    cells.iter().map(ToString::to_string).collect()
+}
+
 
 pub struct App {
     opetope: Diagram<Data>,
+    journal: Journal,
+    history: History<(Diagram<Data>, Journal)>,
 
+    workspace: Workspace,
     layout: Layout,
+    keys: Keys,
+
+    modifiers: iced::keyboard::Modifiers,
 }
 
 pub enum Error {
@@ -41,15 +72,28 @@ pub enum Error {
 
     EmptyName,
     NoHomeDir,
+    NothingSelected,
 
     Deserialization(String),
     PathError(String),
+    Format(format::Error),
+    Export(String),
+    Script(script::Error),
+    Journal(journal::Error),
+    Keys { file: PathBuf, reason: String },
+
+    Invalid { file: PathBuf, violations: Vec<Violation> },
 }
 impl From<opetope::Error> for Error {
     fn from(e: opetope::Error) -> Self {
         Self::Opetope(e)
     }
 }
+impl From<format::Error> for Error {
+    fn from(e: format::Error) -> Self {
+        Self::Format(e)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum GlobalMessage {
@@ -57,8 +101,15 @@ pub enum GlobalMessage {
     Opetope(opetope::Message),
 
     Layout(main_layout::Message),
+    Replay(replay::Message),
+    Viewport(viewport::Message),
+    Levels(level_bar::Message),
 
     FocusNext,
+    SelectAll,
+    Navigate(opetope::Direction),
+    SelectFocused,
+    ModifiersChanged(iced::keyboard::Modifiers),
 
     Ticked,
     Idle,
@@ -72,12 +123,19 @@ impl Default for GlobalMessage {
 
 impl Default for App {
     fn default() -> Self {
-        let opetope = opetope::Tower::init("0".to_string().into()).1.into_next().unwrap();
+        let (opetope, journal) =
+        Journal::started(opetope::Tower::init("0".to_string().into()).1.into_next().unwrap())
+            .unwrap();
 
         Self {
             layout: fill![],
+            history: fill![],
+            workspace: fill![],
+            keys: fill![],
+            modifiers: fill![],
 
             opetope,
+            journal,
         }
     }
 }
@@ -85,76 +143,247 @@ impl Default for App {
 
 
 impl App {
+    /// Applies `op` to the opetope as a single undoable step, recording the journal entries it
+    /// returns. On failure, the opetope is restored.
+    ///
+    fn edit(&mut self, op: impl FnOnce(&mut Diagram<Data>) -> Result<Vec<Entry>, Error>) {
+        let before = self.opetope.clone();
+
+        match op(&mut self.opetope) {
+            Ok(entries) => {
+                self.history.record((before, self.journal.clone()));
+                self.journal.record(entries);
+
+                self.workspace.dirty = true;
+            },
+
+            Err(e) => {
+                self.opetope = before;
+
+                self.error(e);
+            },
+        }
+    }
+
+    fn undo(&mut self) {
+        self.layout.state.take();
+
+        if self.history.can_undo() {
+            let mut current = (self.opetope.clone(), self.journal.clone());
+
+            self.history.undo(&mut current);
+            self.restore(current);
+        }
+    }
+
+    fn redo(&mut self) {
+        self.layout.state.take();
+
+        if self.history.can_redo() {
+            let mut current = (self.opetope.clone(), self.journal.clone());
+
+            self.history.redo(&mut current);
+            self.restore(current);
+        }
+    }
+
+    /// Applies `op` to the selection. A changed selection is recorded in the history, so that undo
+    /// restores it too, but unlike an edit it doesn't modify the opetope.
+    ///
+    fn reselect(&mut self, op: impl FnOnce(&mut Diagram<Data>) -> Result<(), opetope::Error>) {
+        let before = self.opetope.clone();
+
+        match op(&mut self.opetope) {
+            Ok(()) => {
+                if self.opetope.selected_cells() != before.selected_cells() {
+                    self.history.record((before, self.journal.clone()));
+                }
+            },
+
+            Err(e) => self.error(e.into()),
+        }
+    }
+
+    fn restore(&mut self, (opetope, journal): (Diagram<Data>, Journal)) {
+        // NOTE: Undoing a change of the selection leaves the journal, and thus the opetope, as it was.
+        //
+        if journal != self.journal {
+            self.workspace.dirty = true;
+        }
+
+        self.opetope = opetope;
+        self.journal = journal;
+    }
+
     fn extrude(&mut self, name: Data, wrap: Data) {
         if let Some(sel) = self.opetope.selected_cells() {
-            match
-            self.opetope
-                .extrude(&sel, name, wrap)
-                .ok()
-            {
-                Ok(_) => {},
-
-                Err(e) => self.error(e.into()),
-            }
+            let edit = Edit::Extrude { cells: names(&sel.as_cells()), group: name.name(), wrap: wrap.name() };
+
+            self.edit(|opetope| {
+                let interaction = opetope.extrude(&sel, name, wrap).ok()?;
+
+                Ok(vec![Entry::new(edit, vec![interaction])])
+            });
         }
     }
 
     fn split(&mut self, name: Data, wrap_top: Data, wrap_bot: Data) {
         if let Some(sel) = self.opetope.selected_cells() {
-            match
-            self.opetope
-                .split(&sel, name, wrap_top, wrap_bot)
-                .ok()
-            {
-                Ok(_) => {},
-
-                Err(e) => self.error(e.into()),
-            }
+            let edit = Edit::Split { cells: names(&sel.as_cells()), group: name.name(), wrap_top: wrap_top.name(), wrap_bot: wrap_bot.name() };
+
+            self.edit(|opetope| {
+                let interaction = opetope.split(&sel, name, wrap_top, wrap_bot).ok()?;
+
+                Ok(vec![Entry::new(edit, vec![interaction])])
+            });
         }
     }
 
     fn sprout(&mut self, data: Vec<(opetope::ViewIndex, Data, Data)>) {
         if self.opetope.selected_cells().is_some() {
-            for (cell, name, wrap) in data {
-                match
-                self.opetope
-                    .sprout(&cell, name.into(), wrap.into())
-                    .ok()
-                {
-                    Ok(_) => {},
+            self.edit(|opetope| {
+                let mut entries = vec![];
+
+                for (cell, name, wrap) in data {
+                    let edit = Edit::Sprout { cell: cell.to_string(), end: name.name(), wrap: wrap.name() };
 
-                    Err(e) => self.error(e.into()),
+                    let interaction =
+                    opetope
+                        .sprout(&cell, name, wrap)
+                        .ok()?;
+
+                    entries.push(Entry::new(edit, vec![interaction]));
                 }
-            }
+
+                Ok(entries)
+            });
         }
     }
 
-    fn cut(&mut self) {
-        // let mut error = None;
+    fn pass(&mut self, wraps: Vec<opetope::MetaCell<Data>>) {
+        let edit =
+        Edit::Pass {
+            wraps: wraps
+                .iter()
+                .map(|wrap| (wrap.face.fill.to_string(), wrap.data.name()))
+                .collect(),
+        };
 
-        // take_mut::take(&mut self.opetope, |opetope| {
-        //     let backup = opetope.clone();
+        self.edit(|opetope| {
+            opetope.into_next(wraps)?;
 
-        //     match opetope.retain_selected() {
-        //         Ok(None) =>
-        //             opetope::Tower::init("0".to_string().into()).1.into_next().unwrap(),
+            Ok(vec![Entry::new(edit, vec![])])
+        });
+    }
 
-        //         Ok(Some(restricted)) =>
-        //             restricted.to_diagram(),
+    fn select(&mut self, cell: opetope::ViewIndex) {
+        let before =
+        self.opetope
+            .selected_cells()
+            .map(|selection| selection.as_cells())
+            .unwrap_or_default();
+
+        self.reselect(|opetope|
+            opetope
+                .select(&cell)
+                .map(|_| ())
+        );
+
+        let after =
+        self.opetope
+            .selected_cells()
+            .map(|selection| selection.as_cells())
+            .unwrap_or_default();
+
+        // NOTE: Clicking a cell outside the cell space of the selection drops the selected cells,
+        // which is easy to miss in a large diagram.
+        //
+        let dropped =
+        before
+            .iter()
+            .filter(|selected| **selected != cell && !after.contains(selected))
+            .count();
+
+        if dropped > 0 && after.contains(&cell) {
+            self.layout.notice(format!["Selection reset: {} isn't in the cell space of the selected cells", cell]);
+        }
+    }
+
+    fn select_range(&mut self, cell: opetope::ViewIndex) {
+        self.reselect(|opetope|
+            opetope
+                .select_range(&cell)
+                .map(|_| ())
+        );
+    }
+
+    /// Moves the keyboard focus from the focused cell, or onto the first cell of the opetope.
+    ///
+    fn navigate(&mut self, direction: opetope::Direction) {
+        let next =
+        match self.opetope.focused_cell() {
+            Some(cell) => self.opetope.neighbour(&cell, direction),
+
+            None => self.opetope.indices().into_iter().next(),
+        };
+
+        if let Some(next) = next {
+            if let Err(e) = self.opetope.focus(&next) {
+                self.error(e.into());
+            }
+        }
+    }
+
+    fn select_focused(&mut self) {
+        if let Some(cell) = self.opetope.focused_cell() {
+            if self.modifiers.shift {
+                self.select_range(cell);
+
+            } else {
+                self.select(cell);
+            }
+        }
+    }
 
-        //         Err(e) => {
-        //             error = Some(e);
+    fn select_all(&mut self) {
+        self.reselect(|opetope| {
+            opetope.select_all();
 
-        //             backup
-        //         },
-        //     }
-        // });
+            Ok(())
+        });
+    }
 
-        self.opetope = opetope::Tower::init("0".to_string().into()).1.into_next().unwrap();
+    fn cut(&mut self) {
+        let restarted =
+        self.opetope
+            .retain_selected()
+            .map_err(Error::from)
+            .and_then(|restricted| {
+                let opetope =
+                restricted
+                    .ok_or(Error::NothingSelected)?
+                    .to_diagram();
+
+                // NOTE: A cut can't be replayed, so the journal starts anew from its result.
+                //
+                Journal::started(opetope).map_err(Error::from)
+            });
+
+        match restarted {
+            Ok((opetope, journal)) => {
+                let before = (
+                    std::mem::replace(&mut self.opetope, opetope),
+                    std::mem::replace(&mut self.journal, journal),
+                );
+
+                self.history.record(before);
+
+                self.workspace.dirty = true;
+            },
 
-        // if let Some(e) = error {
-        //     self.error(e.into());
-        // }
+            Err(e) =>
+                self.error(e),
+        }
     }
 
     fn prepare_rename(&mut self) {
@@ -172,63 +401,173 @@ impl App {
     fn rename(&mut self, new_names: Vec<Data>) {
         let sel = self.opetope.selected_cells().unwrap();
 
-        for (cell, new_name) in sel.as_cells().iter().zip(new_names) {
-            match self.opetope.rename(cell, new_name) {
-                Ok(_) => {},
+        self.edit(|opetope| {
+            let mut entries = vec![];
+
+            for (cell, new_name) in sel.as_cells().iter().zip(new_names) {
+                let edit = Edit::Rename { cell: cell.to_string(), name: new_name.name() };
+
+                opetope.rename(cell, new_name)?;
 
-                Err(e) => self.error(e.into()),
+                entries.push(Entry::new(edit, vec![]));
             }
+
+            Ok(entries)
+        });
+    }
+
+    fn delete(&mut self) {
+        if let Some(sel) = self.opetope.selected_cells() {
+            self.edit(|opetope| {
+                let mut entries = vec![];
+
+                for cell in sel.as_cells() {
+                    let edit = Edit::Delete { cell: cell.to_string() };
+
+                    let interaction =
+                    opetope
+                        .delete(&cell)
+                        .ok()?;
+
+                    entries.push(Entry::new(edit, vec![interaction]));
+                }
+
+                Ok(entries)
+            });
+        }
+    }
+
+    fn ungroup(&mut self) {
+        if let Some(sel) = self.opetope.selected_cells() {
+            self.edit(|opetope| {
+                let mut entries = vec![];
+
+                for group in sel.as_cells() {
+                    let edit = Edit::Ungroup { group: group.to_string() };
+
+                    let interaction =
+                    opetope
+                        .ungroup(&group)
+                        .ok()?;
+
+                    entries.push(Entry::new(edit, vec![interaction]));
+                }
+
+                Ok(entries)
+            });
         }
     }
 
-    // TODO: Custom workspace dirs
-    //
     fn save(&mut self) {
-        use std::fs::File;
+        match self.workspace.file().map(Path::to_path_buf) {
+            Some(path) =>
+                self.save_to(path),
 
-        use std::io::Write;
-        use serde_json::ser;
+            None =>
+                self.prepare_save_as(),
+        }
+    }
 
-        match home::home_dir() {
-            Some(mut path) => {
-                path.push("opetope.json");
-                let mut f = File::create(path).unwrap();
+    fn prepare_save_as(&mut self) {
+        let path =
+        self.workspace
+            .file()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
 
-                f.write(ser::to_string(&self.opetope).unwrap().as_bytes()).unwrap();
-            },
+        self.layout.state = State::save_as(path);
+    }
+
+    fn save_to(&mut self, path: PathBuf) {
+        let saved =
+        format::to_string_with_journal(&self.opetope, &self.journal)
+            .map_err(|e| Error::PathError(e.to_string()))
+            .and_then(|contents| workspace::write(&path, &contents))
+            .and_then(|_| self.workspace.set_file(path));
 
-           None =>
-                self.error(Error::NoHomeDir),
+        if let Err(e) = saved {
+            self.error(e);
         }
     }
 
-    // TODO: Custom workspace dirs
-    //
-    fn load(&mut self) {
-        use std::fs::File;
+    fn prepare_export(&mut self) {
+        let path =
+        self.workspace
+            .file()
+            .map(|path| path.with_extension("svg").display().to_string())
+            .unwrap_or_default();
+
+        self.layout.state = State::export(path);
+    }
+
+    fn export(&mut self, path: PathBuf) {
+        let exported =
+        export::Format::from_path(&path)
+            .map_err(|e| Error::Export(e.to_string()))
+            .and_then(|format| {
+                let picture = export::export(&self.opetope, format, &export::Palette::default());
 
-        use std::io::Read;
-        use serde_json::de;
+                workspace::write(&path, &picture)
+            });
 
-        match home::home_dir() {
-            Some(mut path) => {
-                path.push("opetope.json");
-                let mut f = File::open(path).unwrap();
+        if let Err(e) = exported {
+            self.error(e);
+        }
+    }
 
-                let mut buf = String::new();
-                f.read_to_string(&mut buf).unwrap();
+    fn run_script(&mut self, path: PathBuf) {
+        let script =
+        workspace::read(&path)
+            .and_then(|source| Script::parse(&source).map_err(Error::Script));
 
-                match de::from_str(&buf) {
-                    Ok(op) =>
-                        self.opetope = op,
+        match script {
+            Ok(script) =>
+                self.edit(|opetope| script.run(opetope).map_err(Error::Script)),
 
-                    Err(e) =>
-                        self.error(Error::Deserialization(e.to_string())),
+            Err(e) =>
+                self.error(e),
+        }
+    }
+
+    fn replay(&mut self) {
+        self.layout.state.take();
+
+        match Replay::new(&self.journal) {
+            Ok(replay) =>
+                self.layout.replay = Some(replay),
+
+            Err(e) =>
+                self.error(Error::Journal(e)),
+        }
+    }
+
+    fn open(&mut self, path: PathBuf) {
+        let opened: Result<(Diagram<Data>, Journal), Error> =
+        workspace::read(&path)
+            .and_then(|contents|
+                format::from_str_with_journal(&contents)
+                    .map_err(|e| Error::Deserialization(format!["{}: {}", path.display(), e]))
+            )
+            .and_then(|(opetope, journal): (Diagram<Data>, Journal)|
+                opetope
+                    .validate()
+                    .map(|_| (opetope, journal))
+                    .map_err(|violations| Error::Invalid { file: path.clone(), violations })
+            );
+
+        match opened {
+            Ok((opetope, journal)) => {
+                self.opetope = opetope;
+                self.journal = journal;
+                self.history = fill![];
+
+                if let Err(e) = self.workspace.set_file(path) {
+                    self.error(e);
                 }
             },
 
-            None =>
-                self.error(Error::NoHomeDir),
+            Err(e) =>
+                self.error(e),
         }
     }
 
@@ -244,20 +583,51 @@ impl Application for App {
     type Flags = ();
 
     fn new(_: Self::Flags) -> (Self, Command<Self::Message>) {
+        let mut app = Self::default();
+
+        match Workspace::load() {
+            Ok(workspace) =>
+                app.workspace = workspace,
+
+            Err(e) =>
+                app.error(e),
+        }
+
+        match Keys::load() {
+            Ok(keys) =>
+                app.keys = keys,
+
+            Err(e) =>
+                app.error(e),
+        }
+
         (
-            fill![],
+            app,
             Command::none(),
         )
     }
 
     fn title(&self) -> String {
-        "eru".into()
+        self.workspace.title()
     }
 
     fn update(&mut self, message: Self::Message, _: &mut iced::Clipboard) -> Command<Self::Message> {
         match message {
             GlobalMessage::Idle => {},
 
+            // NOTE: The replayed states can't be edited.
+            //
+            GlobalMessage::Sidebar(_) | GlobalMessage::Opetope(_) | GlobalMessage::SelectAll | GlobalMessage::Navigate(_) | GlobalMessage::SelectFocused
+                if self.layout.replay.is_some() => {},
+
+            GlobalMessage::Replay(replay::Message::Exit) =>
+                self.layout.replay = None,
+
+            GlobalMessage::Replay(msg) =>
+                if let Some(replay) = &mut self.layout.replay {
+                    replay.update(msg);
+                },
+
             GlobalMessage::Sidebar(msg) =>
                 match msg {
                     sidebar::Message::Pass => {
@@ -268,7 +638,7 @@ impl Application for App {
                             .collect();
 
                         if groups_left.is_empty() {
-                            self.opetope.into_next(vec![]).unwrap();
+                            self.pass(vec![]);
 
                         } else {
                             self.layout.state = State::pass(groups_left);
@@ -311,11 +681,35 @@ impl Application for App {
                     sidebar::Message::Rename =>
                         self.prepare_rename(),
 
+                    sidebar::Message::Delete =>
+                        self.delete(),
+
+                    sidebar::Message::Ungroup =>
+                        self.ungroup(),
+
                     sidebar::Message::Save =>
                         self.save(),
 
+                    sidebar::Message::SaveAs =>
+                        self.prepare_save_as(),
+
                     sidebar::Message::Load =>
-                        self.load(),
+                        self.layout.state = State::open(self.workspace.recent().to_vec()),
+
+                    sidebar::Message::Export =>
+                        self.prepare_export(),
+
+                    sidebar::Message::Script =>
+                        self.layout.state = State::script(),
+
+                    sidebar::Message::Replay =>
+                        self.replay(),
+
+                    sidebar::Message::Undo =>
+                        self.undo(),
+
+                    sidebar::Message::Redo =>
+                        self.redo(),
                 },
 
 
@@ -323,7 +717,7 @@ impl Application for App {
                 match msg {
                     main_layout::Message::UpdatedName(new_name) =>
                         match &mut self.layout.state {
-                            State::Default | State::ProvidePass { .. } =>
+                            State::Default | State::ProvidePass { .. } | State::SaveAs { .. } | State::Open { .. } | State::Export { .. } | State::Script { .. } =>
                                 unreachable![],
 
                             State::Rename { remaining, .. } =>
@@ -341,7 +735,7 @@ impl Application for App {
 
                     main_layout::Message::UpdatedFirstWrap(new_name) =>
                         match &mut self.layout.state {
-                            State::Default | State::Rename { .. } =>
+                            State::Default | State::Rename { .. } | State::SaveAs { .. } | State::Open { .. } | State::Export { .. } | State::Script { .. } =>
                                 unreachable![],
 
                             State::ProvideExtrude { wrap, .. } =>
@@ -359,7 +753,7 @@ impl Application for App {
 
                     main_layout::Message::UpdatedSecondWrap(new_name) =>
                         match &mut self.layout.state {
-                            State::Default | State::ProvidePass { .. } | State::ProvideSprout { .. } | State::Rename { .. } =>
+                            State::Default | State::ProvidePass { .. } | State::ProvideSprout { .. } | State::Rename { .. } | State::SaveAs { .. } | State::Open { .. } | State::Export { .. } | State::Script { .. } =>
                                 unreachable![],
 
                             State::ProvideExtrude { wrap, .. } =>
@@ -369,6 +763,21 @@ impl Application for App {
                                 wrap_bot.value = new_name,
                         }
 
+                    main_layout::Message::UpdatedPath(new_path) =>
+                        match &mut self.layout.state {
+                            State::SaveAs { path, .. } | State::Open { path, .. } | State::Export { path, .. } | State::Script { path, .. } =>
+                                path.value = new_path,
+
+                            _ =>
+                                unreachable![],
+                        }
+
+                    main_layout::Message::OpenRecent(path) => {
+                        self.layout.state.take();
+
+                        self.open(path);
+                    },
+
                     main_layout::Message::ConfirmPopUp =>
                         match self.layout.state.take() {
                             State::Default =>
@@ -439,12 +848,7 @@ impl Application for App {
                                     });
 
                                     if groups_left.is_empty() {
-                                        match self.opetope.into_next(wraps) {
-                                            Ok(_) => {},
-
-                                            Err(e) =>
-                                                self.error(e.into()),
-                                        }
+                                        self.pass(wraps);
 
                                     } else {
                                         let last = fill![];
@@ -452,6 +856,34 @@ impl Application for App {
                                         self.layout.state = State::ProvidePass { groups_left, wraps, last, pop_up };
                                     }
                                 },
+
+                            State::SaveAs { path, .. } =>
+                                match workspace::expand(&path.value) {
+                                    Ok(path) => self.save_to(path),
+
+                                    Err(e) => self.error(e),
+                                },
+
+                            State::Open { path, .. } =>
+                                match workspace::expand(&path.value) {
+                                    Ok(path) => self.open(path),
+
+                                    Err(e) => self.error(e),
+                                },
+
+                            State::Export { path, .. } =>
+                                match workspace::expand(&path.value) {
+                                    Ok(path) => self.export(path),
+
+                                    Err(e) => self.error(e),
+                                },
+
+                            State::Script { path, .. } =>
+                                match workspace::expand(&path.value) {
+                                    Ok(path) => self.run_script(path),
+
+                                    Err(e) => self.error(e),
+                                },
                         },
 
                     main_layout::Message::ExitPopUp => {
@@ -463,11 +895,64 @@ impl Application for App {
                 match msg {
                     opetope::Message::Idle => unreachable!["idle message"],
 
-                    opetope::Message::Select(cell) => {
-                        self.opetope.select(&cell).unwrap();
-                    },
+                    opetope::Message::Select(cell) if self.modifiers.shift =>
+                        self.select_range(cell),
+
+                    opetope::Message::Select(cell) =>
+                        self.select(cell),
+
+                    opetope::Message::Hover(cell) =>
+                        self.layout.hovered = Some(cell),
+
+                    // NOTE: Another cell may have been hovered since.
+                    //
+                    opetope::Message::Unhover(cell) =>
+                        if self.layout.hovered.as_ref() == Some(&cell) {
+                            self.layout.hovered = None;
+                        },
+                },
+
+            GlobalMessage::SelectAll =>
+                self.select_all(),
+
+            GlobalMessage::Viewport(msg) => {
+                let selection =
+                self.opetope
+                    .selected_cells()
+                    .map(|selection| selection.as_cells())
+                    .unwrap_or_default();
+
+                self.layout.viewport.update(msg, &self.opetope.layout_folded(&self.layout.levels), &selection);
+            },
+
+            GlobalMessage::Levels(msg) =>
+                match msg {
+                    level_bar::Message::Toggle(level) =>
+                        self.layout.levels.toggle(level),
+
+                    level_bar::Message::Focus(level) =>
+                        self.layout.levels.toggle_focus(level),
+
+                    level_bar::Message::ToggleBoundary =>
+                        self.layout.boundary = !self.layout.boundary,
+
+                    level_bar::Message::ToggleDrawing =>
+                        self.layout.drawing =
+                        match self.layout.drawing {
+                            Drawing::Canvas => Drawing::Widgets,
+                            Drawing::Widgets => Drawing::Canvas,
+                        },
                 },
 
+            GlobalMessage::Navigate(direction) =>
+                self.navigate(direction),
+
+            GlobalMessage::SelectFocused =>
+                self.select_focused(),
+
+            GlobalMessage::ModifiersChanged(modifiers) =>
+                self.modifiers = modifiers,
+
             GlobalMessage::FocusNext =>
                 match &mut self.layout.state {
                     State::ProvideExtrude { name, wrap, .. } | State::ProvideSprout { last_end: name, last_wrap: wrap, .. } =>
@@ -500,6 +985,9 @@ impl Application for App {
                     State::Rename { remaining, .. } =>
                         remaining.last_mut().unwrap().state.focus(),
 
+                    State::SaveAs { path, .. } | State::Open { path, .. } | State::Export { path, .. } | State::Script { path, .. } =>
+                        path.state.focus(),
+
                     _ => {},
                 },
             
@@ -512,6 +1000,9 @@ impl Application for App {
 
     fn subscription(&self) -> iced::Subscription<Self::Message> {
         let not_editing = matches![self.layout.state, State::Default];
+        let replaying = self.layout.replay.is_some();
+
+        let bindings = self.keys.clone();
 
         iced::Subscription::batch(vec![
             iced_native::subscription::events_with(move |e, _| {
@@ -520,8 +1011,17 @@ impl Application for App {
                 match e {
                     Event::Keyboard(key) =>
                         match key {
-                            iced::keyboard::Event::KeyPressed { key_code, .. } =>
+                            iced::keyboard::Event::KeyPressed { key_code, modifiers } =>
                                 match key_code {
+                                    iced::keyboard::KeyCode::Left if replaying =>
+                                        Some(GlobalMessage::Replay(replay::Message::Back)),
+
+                                    iced::keyboard::KeyCode::Right if replaying =>
+                                        Some(GlobalMessage::Replay(replay::Message::Forward)),
+
+                                    iced::keyboard::KeyCode::Escape if replaying =>
+                                        Some(GlobalMessage::Replay(replay::Message::Exit)),
+
                                     iced::keyboard::KeyCode::Tab =>
                                         Some(GlobalMessage::FocusNext),
 
@@ -531,19 +1031,15 @@ impl Application for App {
                                     iced::keyboard::KeyCode::Escape =>
                                         Some(GlobalMessage::Layout(main_layout::Message::ExitPopUp)),
 
-                                    iced::keyboard::KeyCode::E if not_editing =>
-                                        Some(GlobalMessage::Sidebar(sidebar::Message::Enclose)),
-
-                                    iced::keyboard::KeyCode::S if not_editing =>
-                                        Some(GlobalMessage::Sidebar(sidebar::Message::Sprout)),
-
-                                    iced::keyboard::KeyCode::N if not_editing =>
-                                        Some(GlobalMessage::Sidebar(sidebar::Message::Pass)),
-
                                     _ =>
-                                        None,
+                                        bindings.command(key_code, modifiers)
+                                            .filter(|command| not_editing || command.while_editing())
+                                            .map(keys::Command::message),
                                 },
 
+                            iced::keyboard::Event::ModifiersChanged(modifiers) =>
+                                Some(GlobalMessage::ModifiersChanged(modifiers)),
+
                             _ =>
                                 None,
                         },
@@ -564,42 +1060,6 @@ impl Application for App {
 
 
 
-impl fmt::Display for opetope::Error {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            opetope::Error::CannotSproutGroup(_cell) =>
-                write![fmt, "Cannot sprout group"],
-
-            opetope::Error::CannotGroupDisconnected(_sel) =>
-                write![fmt, "Cells are not connected"],
-
-            opetope::Error::CellsDoNotFormTree(_sel) =>
-                write![fmt, "Cells do not form a tree"],
-
-
-            // Internal
-            //
-            opetope::Error::IndexError(e) =>
-                write![fmt, "INTERNAL: error while indexing: {:?}", e],
-
-            opetope::Error::TooMuchDepth(depth) =>
-                write![fmt, "INTERNAL: too much depth: {}", depth],
-
-            opetope::Error::NoSuchCell(cell) =>
-                write![fmt, "INTERNAL: cell does not exist: {}", cell],
-
-            opetope::Error::NoCellWithInputs(inputs) =>
-                write![fmt, "INTERNAL: no cell with inputs: {:?}", inputs],
-
-            opetope::Error::CannotSplitBoundaryCells(sel) =>
-                write![fmt, "INTERNAL: cannot split boundary cells: {:?}", sel],
-
-            opetope::Error::CannotExtrudeNestedCells(sel) =>
-                write![fmt, "INTERNAL: cannot extrude nested cells: {:?}", sel],
-
-        }
-    }
-}
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -607,9 +1067,29 @@ impl fmt::Display for Error {
             
             Self::EmptyName => write![fmt, "Cell name cannot be empty"],
             Self::NoHomeDir => write![fmt, "Unknown platform, no home directory found"],
+            Self::NothingSelected => write![fmt, "Nothing is selected"],
 
             Self::Deserialization(e) => write![fmt, "Deserialization error: {}", e],
             Self::PathError(e) => write![fmt, "File error: {}", e],
+            Self::Format(e) => write![fmt, "{}", e],
+            Self::Export(e) => write![fmt, "Export error: {}", e],
+            Self::Script(e) => write![fmt, "Script error at {}", e],
+            Self::Journal(e) => write![fmt, "{}", e],
+            Self::Keys { file, reason } => write![fmt, "Invalid key bindings in {}: {}. The default bindings are used instead.", file.display(), reason],
+
+            Self::Invalid { file, violations } => {
+                write![fmt, "{} is not a valid opetope:", file.display()]?;
+
+                for violation in violations.iter().take(SHOWN_VIOLATIONS) {
+                    write![fmt, "\n- {}", violation]?;
+                }
+
+                if violations.len() > SHOWN_VIOLATIONS {
+                    write![fmt, "\n(and {} more)", violations.len() - SHOWN_VIOLATIONS]?;
+                }
+
+                Ok(())
+            },
         }
     }
 }
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use iced::{
+    canvas::{ self, event, Canvas, Cursor, Event, Frame, Geometry, Path, Stroke },
+    mouse, HorizontalAlignment, Length, Point, Rectangle, Size, VerticalAlignment,
+};
+
+use eru::model::{ Render, Zoom, Levels, Fold };
+use eru::styles::{
+    container::{ cell, color },
+    metrics::LABEL_SIZE,
+    text::fonts,
+};
+
+use eru::opetope::{
+    data::{ Highlight, Selectable, DATA_HEIGHT },
+    format::Named,
+    layout::{ Layout, Shape, Rect },
+    viewing::{ Message, ViewIndex },
+    Diagram,
+};
+
+
+
+/// State of a cell which changes how it's drawn.
+///
+#[derive(Debug, Clone, Copy, Default)]
+struct Look {
+    selected: bool,
+    focused: bool,
+    hovered: bool,
+
+    highlight: Option<Highlight>,
+}
+
+/// Draws a laid out opetope on a single canvas, in place of the nested widgets of
+/// `Diagram::view`. Clicks and hovers are mapped back to cells through the layout.
+///
+pub struct Cells {
+    layout: Layout,
+    looks: HashMap<ViewIndex, Look>,
+
+    hovered: Option<ViewIndex>,
+
+    levels: Levels,
+    render: Render,
+    zoom: Zoom,
+}
+
+
+
+impl Cells {
+    /// Draws the opetope like `Diagram::view` does, on a canvas.
+    ///
+    pub fn view<Data: Clone + Named>(
+        opetope: &Diagram<Selectable<Data>>,
+
+        render: Render,
+        zoom: Zoom,
+        levels: &Levels,
+    ) -> iced::Element<'static, Message>
+    {
+        let layout = opetope.layout_folded(levels);
+
+        let looks: HashMap<_, _> =
+        layout.shapes
+            .iter()
+            .filter_map(|shape|
+                match shape {
+                    Shape::Cell { index, .. } => Some(index),
+                    Shape::Line(_) => None,
+                }
+            )
+            .filter_map(|index| {
+                let data = opetope.cell(index).ok()?.to_data();
+
+                let look = Look {
+                    selected: data.selected(),
+                    focused: data.focused(),
+                    hovered: data.hovered(),
+
+                    highlight: data.highlighted(),
+                };
+
+                Some((index.clone(), look))
+            })
+            .collect();
+
+        let hovered =
+        looks
+            .iter()
+            .find(|(_, look)| look.hovered)
+            .map(|(index, _)| index.clone());
+
+        let width = zoom.scale(layout.width.ceil() as u16);
+        let height = zoom.scale(layout.height.ceil() as u16);
+
+        Canvas::new(Cells {
+            layout,
+            looks,
+
+            hovered,
+
+            levels: levels.clone(),
+            render,
+            zoom,
+        })
+            .width(Length::Units(width))
+            .height(Length::Units(height))
+            .into()
+    }
+
+    /// The cell under the point of the canvas, unless its level is only a thumbnail.
+    ///
+    fn cell_at(&self, point: Point) -> Option<ViewIndex> {
+        let factor = self.zoom.factor();
+
+        let index = self.layout.cell_at(point.x / factor, point.y / factor)?;
+        let frame = self.layout.frame(index)?;
+
+        if self.levels.fold(frame.level) == Fold::Shown {
+            Some(index.clone())
+
+        } else {
+            None
+        }
+    }
+
+    fn draw_cell(&self, frame: &mut Frame, index: &ViewIndex, label: &str, bounds: Rect, data: Rect) {
+        let factor = self.zoom.factor();
+
+        // NOTE: Cells of thumbnails are smaller than the rest, by the ratio of their label height.
+        //
+        let scale = factor * data.height / DATA_HEIGHT as f32;
+
+        let look = self.looks.get(index).copied().unwrap_or_default();
+
+        let background =
+        if look.selected {
+            color::SELECTED
+
+        } else if let Some(highlight) = look.highlight {
+            highlight.color()
+
+        } else {
+            color![color::rgb::BACKGROUND[0], color::rgb::BACKGROUND[1], color::rgb::BACKGROUND[2]]
+        };
+
+        let border =
+        if look.focused {
+            color::FOCUSED
+
+        } else {
+            color![color::rgb::BORDER[0], color::rgb::BORDER[1], color::rgb::BORDER[2]]
+        };
+
+        let border_width =
+        if look.hovered {
+            1.5 * cell::WIDTH * scale
+
+        } else {
+            cell::WIDTH * scale
+        };
+
+        let outline = rounded_rectangle(bounds.scaled(factor), cell::RADIUS * scale);
+
+        frame.fill(&outline, background);
+        frame.stroke(&outline, Stroke { color: border, width: border_width, ..Stroke::default() });
+
+        let (x, y) = data.scaled(factor).center();
+
+        frame.fill_text(canvas::Text {
+            content: label.to_string(),
+            position: Point::new(x, y),
+            color: color![color::rgb::TEXT[0], color::rgb::TEXT[1], color::rgb::TEXT[2]],
+            size: LABEL_SIZE as f32 * scale,
+            font: fonts::LABEL,
+            horizontal_alignment: HorizontalAlignment::Center,
+            vertical_alignment: VerticalAlignment::Center,
+        });
+    }
+}
+
+impl canvas::Program<Message> for Cells {
+    fn update(&mut self, event: Event, bounds: Rectangle, cursor: Cursor) -> (event::Status, Option<Message>) {
+        if self.render != Render::Interactive {
+            return (event::Status::Ignored, None);
+        }
+
+        let hit =
+        cursor
+            .position_in(&bounds)
+            .and_then(|point| self.cell_at(point));
+
+        // NOTE: Clicks outside of the cells are left to the viewport, which drags the opetope.
+        //
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) =>
+                match hit {
+                    Some(index) => (event::Status::Captured, Some(Message::Select(index))),
+
+                    None => (event::Status::Ignored, None),
+                },
+
+            Event::Mouse(mouse::Event::CursorMoved { .. }) =>
+                match (hit, &self.hovered) {
+                    (Some(index), hovered) if hovered.as_ref() != Some(&index) =>
+                        (event::Status::Ignored, Some(Message::Hover(index))),
+
+                    (None, Some(hovered)) =>
+                        (event::Status::Ignored, Some(Message::Unhover(hovered.clone()))),
+
+                    _ => (event::Status::Ignored, None),
+                },
+
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(bounds.size());
+
+        let factor = self.zoom.factor();
+
+        let line = color![color::rgb::LINE[0], color::rgb::LINE[1], color::rgb::LINE[2]];
+
+        for shape in &self.layout.shapes {
+            match shape {
+                Shape::Line(rect) => {
+                    let rect = rect.scaled(factor);
+
+                    frame.fill(&Path::rectangle(Point::new(rect.x, rect.y), Size::new(rect.width, rect.height)), line);
+                },
+
+                Shape::Cell { index, label, bounds, data } =>
+                    self.draw_cell(&mut frame, index, label, *bounds, *data),
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(&self, bounds: Rectangle, cursor: Cursor) -> mouse::Interaction {
+        let over_cell =
+        self.render == Render::Interactive
+            && cursor
+                .position_in(&bounds)
+                .and_then(|point| self.cell_at(point))
+                .is_some();
+
+        if over_cell {
+            mouse::Interaction::Pointer
+
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+
+fn rounded_rectangle(rect: Rect, radius: f32) -> Path {
+    let Rect { x, y, width, height } = rect;
+
+    let radius = radius.min(width / 2.).min(height / 2.);
+
+    Path::new(|path| {
+        path.move_to(Point::new(x + radius, y));
+
+        path.arc_to(Point::new(x + width, y), Point::new(x + width, y + height), radius);
+        path.arc_to(Point::new(x + width, y + height), Point::new(x, y + height), radius);
+        path.arc_to(Point::new(x, y + height), Point::new(x, y), radius);
+        path.arc_to(Point::new(x, y), Point::new(x + width, y), radius);
+
+        path.close();
+    })
+}
@@ -1,4 +1,4 @@
-use crate::model::Render;
+use eru::model::Render;
 use crate::components::general::Tooltip;
 
 
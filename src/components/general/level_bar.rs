@@ -0,0 +1,109 @@
+use eru::model::{ Levels, Fold, Drawing };
+use eru::styles::container::PADDING;
+
+use crate::components::app::GlobalMessage;
+
+
+
+const TEXT_SIZE: u16 = 16;
+
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Cycles the level between shown, thumbnail and hidden.
+    ///
+    Toggle(usize),
+    Focus(usize),
+
+    /// Highlights the whole boundary of the hovered and selected cells, not only their faces.
+    ///
+    ToggleBoundary,
+
+    /// Switches between drawing the opetope on a canvas and with nested widgets.
+    ///
+    ToggleDrawing,
+}
+
+/// Row of controls above the opetope, which fold each of its levels or focus one of them, choose
+/// how much of the boundary of a cell is highlighted and how the opetope is drawn.
+///
+#[derive(Debug, Default)]
+pub struct LevelBar {
+    controls: Vec<(iced::button::State, iced::button::State)>,
+    boundary: iced::button::State,
+    drawing: iced::button::State,
+}
+
+
+
+impl LevelBar {
+    pub fn view(&mut self, levels: &Levels, boundary: bool, drawing: Drawing, top: usize) -> iced::Element<GlobalMessage> {
+        self.controls.resize_with(top + 1, Default::default);
+
+        let mut controls: Vec<_> =
+        self.controls
+            .iter_mut()
+            .enumerate()
+            .map(|(level, (toggle, focus))| {
+                let fold =
+                match levels.set_fold(level) {
+                    Fold::Shown => "shown",
+                    Fold::Thumbnail => "thumbnail",
+                    Fold::Hidden => "hidden",
+                };
+
+                let focus_label =
+                if levels.focused() == Some(level) {
+                    "Unfocus"
+
+                } else {
+                    "Focus"
+                };
+
+                iced::Row::new()
+                    .spacing(PADDING / 2)
+                    .align_items(iced::Align::Center)
+                    .push(
+                        iced::Button::new(toggle, iced::Text::new(format!["Level {}: {}", level, fold]).size(TEXT_SIZE))
+                            .on_press(GlobalMessage::Levels(Message::Toggle(level)))
+                    )
+                    .push(
+                        iced::Button::new(focus, iced::Text::new(focus_label).size(TEXT_SIZE))
+                            .on_press(GlobalMessage::Levels(Message::Focus(level)))
+                    )
+                    .into()
+            })
+            .collect();
+
+        let boundary_label =
+        if boundary {
+            "Full boundary: on"
+
+        } else {
+            "Full boundary: off"
+        };
+
+        controls.push(
+            iced::Button::new(&mut self.boundary, iced::Text::new(boundary_label).size(TEXT_SIZE))
+                .on_press(GlobalMessage::Levels(Message::ToggleBoundary))
+                .into()
+        );
+
+        let drawing_label =
+        match drawing {
+            Drawing::Canvas => "Drawing: canvas",
+            Drawing::Widgets => "Drawing: widgets",
+        };
+
+        controls.push(
+            iced::Button::new(&mut self.drawing, iced::Text::new(drawing_label).size(TEXT_SIZE))
+                .on_press(GlobalMessage::Levels(Message::ToggleDrawing))
+                .into()
+        );
+
+        iced::Row::with_children(controls)
+            .spacing(2 * PADDING)
+            .into()
+    }
+}
+
@@ -1,12 +1,14 @@
-use crate::styles::container::PADDING;
+use std::path::PathBuf;
 
-use crate::components::{
-    opetope,
+use eru::opetope;
+use eru::model::{ Levels, Drawing };
+use eru::styles::container::PADDING;
 
+use crate::components::{
     app::{ Error, GlobalMessage, Data },
     pop_up::{ self, PopUp, Form },
 
-    general::Sidebar,
+    general::{ Sidebar, Replay, Viewport, viewport, LevelBar, Cells },
 };
 
 
@@ -17,6 +19,9 @@ pub enum Message {
     UpdatedFirstWrap(String),
     UpdatedSecondWrap(String),
 
+    UpdatedPath(String),
+    OpenRecent(PathBuf),
+
     ExitPopUp,
     ConfirmPopUp,
 }
@@ -84,6 +89,31 @@ pub enum State {
         wraps: Vec<opetope::MetaCell<Data>>,
         groups_left: Vec<(opetope::Face, opetope::MetaCell<Data>)>,
     },
+
+    SaveAs {
+        pop_up: pop_up::State,
+
+        path: NameSlot,
+    },
+
+    Open {
+        pop_up: pop_up::State,
+
+        path: NameSlot,
+        recent: Vec<(PathBuf, iced::button::State)>,
+    },
+
+    Export {
+        pop_up: pop_up::State,
+
+        path: NameSlot,
+    },
+
+    Script {
+        pop_up: pop_up::State,
+
+        path: NameSlot,
+    },
 }
 impl Default for State {
     fn default() -> Self {
@@ -113,6 +143,24 @@ impl State {
         Self::Rename { pop_up: fill![], remaining, renamed: vec![] }
     }
 
+    pub fn save_as(path: String) -> Self {
+        Self::SaveAs { pop_up: fill![], path: path.into() }
+    }
+
+    pub fn open(recent: Vec<PathBuf>) -> Self {
+        let recent = recent.into_iter().map(|file| (file, fill![])).collect();
+
+        Self::Open { pop_up: fill![], path: fill![], recent }
+    }
+
+    pub fn export(path: String) -> Self {
+        Self::Export { pop_up: fill![], path: path.into() }
+    }
+
+    pub fn script() -> Self {
+        Self::Script { pop_up: fill![], path: fill![] }
+    }
+
     pub fn take(&mut self) -> Self {
         let mut ret = None;
 
@@ -134,8 +182,24 @@ pub struct Layout {
 
     error: Option<Error>,
 
+    notice_countdown: u64,
+    notice_pop_up: pop_up::State,
+
+    notice: Option<String>,
+
     pub state: State,
+    pub replay: Option<Replay>,
+    pub viewport: viewport::State,
+    pub levels: Levels,
+
+    /// Cell under the cursor, whose face is highlighted.
+    ///
+    pub hovered: Option<opetope::ViewIndex>,
+    pub boundary: bool,
+    pub drawing: Drawing,
+
     sidebar: Sidebar,
+    level_bar: LevelBar,
 }
 
 
@@ -146,26 +210,62 @@ impl Layout {
         self.error_countdown = ERR_DURATION;
     }
 
+    /// Shows a message which, unlike an error, doesn't mean that anything failed.
+    ///
+    pub fn notice(&mut self, message: impl Into<String>) {
+        self.notice = Some(message.into());
+        self.notice_countdown = ERR_DURATION;
+    }
+
     pub fn tick(&mut self) {
         if self.error_countdown > 0 {
             self.error_countdown -= 1;
         }
+
+        if self.notice_countdown > 0 {
+            self.notice_countdown -= 1;
+        }
     }
 
     pub fn view<'app>(&'app mut self, opetope: &'app mut opetope::Diagram<Data>) -> iced::Element<'app, GlobalMessage> {
         let interact =
-        match self.state {
-            State::Default => crate::model::Render::Interactive,
+        match (&self.state, &self.replay) {
+            (State::Default, None) => eru::model::Render::Interactive,
 
-            _ => crate::model::Render::Static,
+            _ => eru::model::Render::Static,
         };
 
         let sidebar = self.sidebar.view(interact).map(GlobalMessage::Sidebar);// TODO: Max height or portion
-        let opetope = opetope.view(interact).map(GlobalMessage::Opetope);
+
+        let zoom = self.viewport.zoom();
+
+        self.levels.fit(opetope.level());
+        opetope.highlight_faces(self.hovered.as_ref(), self.boundary);
+
+        let level_bar = self.level_bar.view(&self.levels, self.boundary, self.drawing, opetope.level());
+
+        let opetope =
+        match &mut self.replay {
+            Some(replay) => replay.view(zoom, &self.levels, self.drawing),
+
+            None => {
+                let cells =
+                match self.drawing {
+                    Drawing::Canvas => Cells::view(opetope, interact, zoom, &self.levels),
+                    Drawing::Widgets => opetope.view(interact, zoom, &self.levels),
+                };
+
+                Viewport::new(&mut self.viewport, cells.map(GlobalMessage::Opetope)).into()
+            },
+        };
 
         let opetope =
-        iced::Container::new(opetope)
-            .padding(PADDING);
+        iced::Container::new(
+            iced::Column::new()
+                .spacing(PADDING)
+                .push(level_bar)
+                .push(opetope)
+        ).padding(PADDING);
 
         let mut main =
         iced::Row::new()
@@ -182,6 +282,14 @@ impl Layout {
             PopUp::new(main, err_msg)
                 .location(pop_up::Location::Top)
                 .view(&mut self.error_pop_up)
+
+        } else if self.notice_countdown > 0 {
+            let notice = Form::notice(self.notice.clone().unwrap());
+
+            main =
+            PopUp::new(main, notice)
+                .location(pop_up::Location::Top)
+                .view(&mut self.notice_pop_up)
         }
 
         match &mut self.state {
@@ -291,6 +399,78 @@ impl Layout {
                             ).padding(PADDING)
                         ),
                 ).view(pop_up),
+
+            State::SaveAs { pop_up, path } =>
+                PopUp::new(
+                    main,
+                    Form::new(GlobalMessage::Layout(Message::ExitPopUp), GlobalMessage::Layout(Message::ConfirmPopUp))
+                        .push(
+                            iced::TextInput::new(
+                                &mut path.state,
+                                "Save as",
+                                &path.value,
+                                |s| GlobalMessage::Layout(Message::UpdatedPath(s)),
+                            ).padding(PADDING)
+                        ),
+                ).view(pop_up),
+
+            State::Export { pop_up, path } =>
+                PopUp::new(
+                    main,
+                    Form::new(GlobalMessage::Layout(Message::ExitPopUp), GlobalMessage::Layout(Message::ConfirmPopUp))
+                        .push(
+                            iced::TextInput::new(
+                                &mut path.state,
+                                "Export to (.svg, .tex)",
+                                &path.value,
+                                |s| GlobalMessage::Layout(Message::UpdatedPath(s)),
+                            ).padding(PADDING)
+                        ),
+                ).view(pop_up),
+
+            State::Script { pop_up, path } =>
+                PopUp::new(
+                    main,
+                    Form::new(GlobalMessage::Layout(Message::ExitPopUp), GlobalMessage::Layout(Message::ConfirmPopUp))
+                        .push(
+                            iced::TextInput::new(
+                                &mut path.state,
+                                "Script file",
+                                &path.value,
+                                |s| GlobalMessage::Layout(Message::UpdatedPath(s)),
+                            ).padding(PADDING)
+                        ),
+                ).view(pop_up),
+
+            State::Open { pop_up, path, recent } => {
+                let recent: Vec<iced::Element<_>> =
+                recent
+                    .iter_mut()
+                    .map(|(file, state)|
+                        iced::Button::new(state, iced::Text::new(file.display().to_string()))
+                            .on_press(GlobalMessage::Layout(Message::OpenRecent(file.clone())))
+                            .style(eru::styles::container::Style::tooltip(iced::Color::BLACK))
+                            .into()
+                    )
+                    .collect();
+
+                PopUp::new(
+                    main,
+                    Form::new(GlobalMessage::Layout(Message::ExitPopUp), GlobalMessage::Layout(Message::ConfirmPopUp))
+                        .push(
+                            iced::TextInput::new(
+                                &mut path.state,
+                                "Open file",
+                                &path.value,
+                                |s| GlobalMessage::Layout(Message::UpdatedPath(s)),
+                            ).padding(PADDING)
+                        )
+                        .push(
+                            iced::Column::with_children(recent)
+                                .spacing(PADDING / 2)
+                        ),
+                ).view(pop_up)
+            },
         }
     }
 }
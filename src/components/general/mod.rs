@@ -2,8 +2,16 @@ pub mod sidebar;
 pub mod tooltip;
 pub mod main_layout;
 pub mod close_button;
+pub mod replay;
+pub mod viewport;
+pub mod level_bar;
+pub mod canvas;
 
 pub use sidebar::Sidebar;
 pub use tooltip::Tooltip;
 pub use main_layout::Layout;
 pub use close_button::CloseButton;
+pub use replay::Replay;
+pub use viewport::Viewport;
+pub use level_bar::LevelBar;
+pub use canvas::Cells;
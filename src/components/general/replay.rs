@@ -0,0 +1,154 @@
+use eru::opetope::{ self, journal::Journal, Diagram };
+use eru::styles::container::PADDING;
+use eru::model::{ Render, Zoom, Levels, Drawing };
+
+use crate::components::{
+    app::{ GlobalMessage, Data },
+
+    general::{ Tooltip, CloseButton, Cells },
+};
+
+
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Forward,
+    Seek(u16),
+
+    Exit,
+}
+
+/// Step-by-step view of the states an opetope went through, as recorded in its journal.
+///
+pub struct Replay {
+    states: Vec<Diagram<Data>>,
+    commands: Vec<String>,
+
+    step: usize,
+
+    back: Tooltip<GlobalMessage>,
+    forward: Tooltip<GlobalMessage>,
+
+    timeline: iced::slider::State,
+    close: iced::button::State,
+}
+
+
+
+impl Replay {
+    pub fn new(journal: &Journal) -> Result<Self, opetope::journal::Error> {
+        let states = journal.replay()?;
+
+        let commands =
+        journal
+            .entries()
+            .iter()
+            .map(|entry| entry.command.to_string())
+            .collect();
+
+        let mut back = Tooltip::from_file("res/img/step_back");
+        back.on_press(GlobalMessage::Replay(Message::Back));
+
+        let mut forward = Tooltip::from_file("res/img/step_forward");
+        forward.on_press(GlobalMessage::Replay(Message::Forward));
+
+        Ok(Self {
+            states,
+            commands,
+
+            step: 0,
+
+            back,
+            forward,
+
+            timeline: fill![],
+            close: fill![],
+        })
+    }
+
+    fn last(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Back =>
+                self.step = self.step.saturating_sub(1),
+
+            Message::Forward =>
+                self.step = (self.step + 1).min(self.last()),
+
+            Message::Seek(step) =>
+                self.step = (step as usize).min(self.last()),
+
+            Message::Exit => {},
+        }
+    }
+
+    pub fn view(&mut self, zoom: Zoom, levels: &Levels, drawing: Drawing) -> iced::Element<GlobalMessage> {
+        let step = self.step;
+        let last = self.last();
+
+        let caption =
+        match step {
+            0 => format!["Start (0/{})", last],
+
+            step => format!["{} ({}/{})", self.commands[step - 1], step, last],
+        };
+
+        let render_back =
+        if step > 0 {
+            Render::InteractiveNoLabel
+
+        } else {
+            Render::Static
+        };
+
+        let render_forward =
+        if step < last {
+            Render::InteractiveNoLabel
+
+        } else {
+            Render::Static
+        };
+
+        // NOTE: The slider's range can't be empty.
+        //
+        let timeline =
+        iced::Slider::new(
+            &mut self.timeline,
+            0..=(last.max(1) as u16),
+            step as u16,
+            |step| GlobalMessage::Replay(Message::Seek(step)),
+        );
+
+        let controls =
+        iced::Row::new()
+            .spacing(PADDING)
+            .align_items(iced::Align::Center)
+            .push(self.back.view(None, render_back))
+            .push(self.forward.view(None, render_forward))
+            .push(timeline)
+            .push(iced::Text::new(caption))
+            .push(
+                CloseButton::cross()
+                    .on_press(GlobalMessage::Replay(Message::Exit))
+                    .view(&mut self.close)
+            );
+
+        let state = &mut self.states[step];
+
+        let diagram =
+        match drawing {
+            Drawing::Canvas => Cells::view(state, Render::Static, zoom, levels),
+            Drawing::Widgets => state.view(Render::Static, zoom, levels),
+        }.map(GlobalMessage::Opetope);
+
+        iced::Column::new()
+            .spacing(PADDING)
+            .push(controls)
+            .push(diagram)
+            .into()
+    }
+}
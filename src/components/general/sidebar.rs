@@ -1,7 +1,7 @@
 use crate::components::general::Tooltip;
 
-use crate::styles::container;
-use crate::model::Render;
+use eru::styles::container;
+use eru::model::Render;
 
 
 
@@ -18,9 +18,18 @@ pub enum Message {
     Pass,
     Cut,
     Rename,
+    Delete,
+    Ungroup,
 
     Save,
+    SaveAs,
     Load,
+    Export,
+    Script,
+    Replay,
+
+    Undo,
+    Redo,
 }
 
 impl Default for Message {
@@ -55,12 +64,22 @@ impl Default for Sidebar {
 
             cut >> Cut,
             rename >> Rename,
+            delete >> Delete,
+            ungroup >> Ungroup,
 
             save >> Save,
+            save_as >> SaveAs,
             load >> Load,
+            export >> Export,
+            script >> Script,
+            replay >> Replay,
+
+            undo >> Undo,
+            redo >> Redo,
         };
 
-        this.tools.insert(5, None);
+        this.tools.insert(13, None);
+        this.tools.insert(7, None);
         this.tools.insert(3, None);
 
         this
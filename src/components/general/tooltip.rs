@@ -2,8 +2,8 @@
 
 use iced::{ button, tooltip };
 
-use crate::behavior::SimpleView;
-use crate::model::{ Icon, Render };
+use eru::behavior::SimpleView;
+use eru::model::{ Icon, Render };
 
 use std::path::PathBuf;
 
@@ -76,7 +76,7 @@ macro_rules! view {
                     $self.icon.view($size)
                         .map(|_| fill![]),
                 )
-                .style(crate::styles::container::Style::tooltip($self.icon.color));
+                .style(eru::styles::container::Style::tooltip($self.icon.color));
 
             #[cfg(not(feature = "glow"))]
             if let Some(size) = $size {
@@ -104,7 +104,7 @@ macro_rules! view {
 
                 } else if let Some(label) = &$self.icon.label {
                     tooltip::Tooltip::new(btn, label, tooltip::Position::FollowCursor)
-                    .style(crate::styles::container::Tooltip)
+                    .style(eru::styles::container::Tooltip)
                     .into()
 
                 } else {
@@ -132,7 +132,7 @@ mod style {
     use iced::Color;
     use iced::button::{ Style, StyleSheet };
 
-    use crate::styles::container::button;
+    use eru::styles::container::button;
 
 
     pub(super) struct Border(pub Color);
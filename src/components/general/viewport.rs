@@ -0,0 +1,340 @@
+use std::hash::Hash;
+
+use iced_native::{
+    event, keyboard, layout, mouse,
+    Clipboard, Element, Event, Hasher, Layout, Length, Point, Rectangle, Size, Vector, Widget,
+};
+use iced_graphics::{ Backend, Defaults, Primitive, Renderer };
+
+use eru::model::{ zoom, Zoom };
+use eru::opetope::{ self, ViewIndex };
+
+use crate::components::app::GlobalMessage;
+
+
+
+/// Distance scrolled by a single line of the mouse wheel.
+///
+const LINE_HEIGHT: f32 = 40.;
+
+/// Space left around the cells shown by `ZoomToSelection`.
+///
+const MARGIN: f32 = 40.;
+
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ZoomIn,
+    ZoomOut,
+
+    /// Zooms by the given number of steps, keeping the point of the viewport in place.
+    ///
+    Wheel { steps: f32, anchor: Point },
+
+    Fit,
+    ZoomToSelection,
+}
+
+/// Zoom and position of the opetope within the space given to it.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct State {
+    zoom: Zoom,
+
+    offset: (f32, f32),
+    drag: Option<Point>,
+    control: bool,
+
+    viewport: (f32, f32),
+    content: (f32, f32),
+}
+
+/// Shows the part of its content given by `State`, which can be dragged around or zoomed with
+/// Ctrl and the mouse wheel.
+///
+pub struct Viewport<'a, B: Backend> {
+    state: &'a mut State,
+
+    content: Element<'a, GlobalMessage, Renderer<B>>,
+}
+
+
+
+impl State {
+    pub const fn zoom(&self) -> Zoom {
+        self.zoom
+    }
+
+    /// Applies the message, where `layout` is the opetope at the natural scale, with its levels
+    /// folded as drawn, and `selection` its selected cells.
+    ///
+    pub fn update(&mut self, message: Message, layout: &opetope::layout::Layout, selection: &[ViewIndex]) {
+        let center = Point::new(self.viewport.0 / 2., self.viewport.1 / 2.);
+
+        match message {
+            Message::ZoomIn =>
+                self.zoom_around(self.zoom.zoomed_in(), center),
+
+            Message::ZoomOut =>
+                self.zoom_around(self.zoom.zoomed_out(), center),
+
+            Message::Wheel { steps, anchor } =>
+                self.zoom_around(Zoom::new(self.zoom.factor() * zoom::STEP.powf(steps)), anchor),
+
+            Message::Fit =>
+                self.show(0., 0., layout.width, layout.height),
+
+            Message::ZoomToSelection =>
+                if let Some(bounds) = layout.bounds(selection) {
+                    self.show(
+                        bounds.x - MARGIN,
+                        bounds.y - MARGIN,
+                        bounds.width + 2. * MARGIN,
+                        bounds.height + 2. * MARGIN,
+                    );
+                },
+        }
+    }
+
+    fn zoom_around(&mut self, zoom: Zoom, anchor: Point) {
+        let ratio = zoom.factor() / self.zoom.factor();
+
+        self.zoom = zoom;
+
+        self.offset = (
+            (self.offset.0 + anchor.x) * ratio - anchor.x,
+            (self.offset.1 + anchor.y) * ratio - anchor.y,
+        );
+
+        self.clamp();
+    }
+
+    /// Zooms so that the area, at the natural scale, fills the viewport, and centers it.
+    ///
+    fn show(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let (view_width, view_height) = self.viewport;
+
+        if width <= 0. || height <= 0. || view_width <= 0. || view_height <= 0. {
+            return;
+        }
+
+        self.zoom = Zoom::new(f32::min(view_width / width, view_height / height));
+
+        let factor = self.zoom.factor();
+
+        self.offset = (
+            x * factor - (view_width - width * factor) / 2.,
+            y * factor - (view_height - height * factor) / 2.,
+        );
+
+        self.clamp();
+    }
+
+    fn scroll(&mut self, dx: f32, dy: f32) {
+        self.offset.0 += dx;
+        self.offset.1 += dy;
+
+        self.clamp();
+    }
+
+    // NOTE: The content size is only known after it is laid out, so it lags one zoom step behind.
+    //
+    fn clamp(&mut self) {
+        let max_x = (self.content.0 - self.viewport.0).max(0.);
+        let max_y = (self.content.1 - self.viewport.1).max(0.);
+
+        self.offset = (
+            self.offset.0.max(0.).min(max_x),
+            self.offset.1.max(0.).min(max_y),
+        );
+    }
+
+    fn offset(&self) -> Vector {
+        Vector::new(self.offset.0, self.offset.1)
+    }
+
+    /// The cursor as seen by the content, which is nowhere if the cursor is outside the viewport.
+    ///
+    fn cursor(&self, cursor_position: Point, bounds: Rectangle) -> Point {
+        if bounds.contains(cursor_position) {
+            cursor_position + self.offset()
+
+        } else {
+            Point::new(-1., -1.)
+        }
+    }
+}
+
+
+impl<'a, B: Backend> Viewport<'a, B> {
+    pub fn new(state: &'a mut State, content: impl Into<Element<'a, GlobalMessage, Renderer<B>>>) -> Self {
+        Self {
+            state,
+
+            content: content.into(),
+        }
+    }
+}
+
+impl<'a, B: Backend> Widget<GlobalMessage, Renderer<B>> for Viewport<'a, B> {
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        Length::Fill
+    }
+
+    fn layout(&self, renderer: &Renderer<B>, limits: &layout::Limits) -> layout::Node {
+        let size =
+        limits
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .resolve(Size::ZERO);
+
+        let content = self.content.layout(renderer, &layout::Limits::new(Size::ZERO, Size::INFINITY));
+
+        layout::Node::with_children(size, vec![content])
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer<B>,
+        defaults: &Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> (Primitive, mouse::Interaction) {
+
+        let bounds = layout.bounds();
+        let offset = self.state.offset();
+
+        let visible = Rectangle {
+            x: bounds.x + offset.x,
+            y: bounds.y + offset.y,
+
+            ..bounds
+        };
+
+        let (content, interaction) =
+        self.content.draw(
+            renderer,
+            defaults,
+            layout.children().next().unwrap(),
+            self.state.cursor(cursor_position, bounds),
+            &visible,
+        );
+
+        let interaction =
+        if self.state.drag.is_some() {
+            mouse::Interaction::Grabbing
+
+        } else {
+            interaction
+        };
+
+        (
+            Primitive::Clip {
+                bounds,
+                offset: Vector::new(offset.x as u32, offset.y as u32),
+                content: Box::new(content),
+            },
+            interaction,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer<B>,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<GlobalMessage>,
+    ) -> event::Status {
+
+        let bounds = layout.bounds();
+        let content = layout.children().next().unwrap().bounds();
+
+        self.state.viewport = (bounds.width, bounds.height);
+        self.state.content = (content.width, content.height);
+
+        let inside = bounds.contains(cursor_position);
+
+        match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) =>
+                self.state.control = modifiers.control,
+
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) if inside => {
+                let (dx, dy) =
+                match delta {
+                    mouse::ScrollDelta::Lines { x, y } => (x * LINE_HEIGHT, y * LINE_HEIGHT),
+                    mouse::ScrollDelta::Pixels { x, y } => (x, y),
+                };
+
+                if self.state.control {
+                    let anchor = Point::new(cursor_position.x - bounds.x, cursor_position.y - bounds.y);
+
+                    messages.push(GlobalMessage::Viewport(Message::Wheel { steps: dy / LINE_HEIGHT, anchor }));
+
+                } else {
+                    self.state.scroll(-dx, -dy);
+                }
+
+                return event::Status::Captured;
+            },
+
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if self.state.drag.is_some() => {
+                let last = self.state.drag.replace(cursor_position).unwrap();
+
+                self.state.scroll(last.x - cursor_position.x, last.y - cursor_position.y);
+
+                return event::Status::Captured;
+            },
+
+            Event::Mouse(mouse::Event::ButtonReleased(_)) if self.state.drag.is_some() => {
+                self.state.drag = None;
+
+                return event::Status::Captured;
+            },
+
+            _ => {},
+        }
+
+        let status =
+        self.content.on_event(
+            event.clone(),
+            layout.children().next().unwrap(),
+            self.state.cursor(cursor_position, bounds),
+            renderer,
+            clipboard,
+            messages,
+        );
+
+        // NOTE: Clicks on the cells select them, the rest of the area drags the opetope.
+        //
+        match (status, event) {
+            (event::Status::Ignored, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)))
+            | (event::Status::Ignored, Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Middle))) if inside => {
+                self.state.drag = Some(cursor_position);
+
+                event::Status::Captured
+            },
+
+            (status, _) => status,
+        }
+    }
+}
+
+impl<'a, B: Backend + 'a> From<Viewport<'a, B>> for Element<'a, GlobalMessage, Renderer<B>> {
+    fn from(viewport: Viewport<'a, B>) -> Self {
+        Element::new(viewport)
+    }
+}
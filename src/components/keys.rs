@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use iced::keyboard::{ KeyCode, Modifiers };
+
+use eru::opetope::Direction;
+
+use crate::components::{
+    app::{ Error, GlobalMessage },
+    general::{ sidebar, viewport, level_bar },
+    workspace::{ self, config_dir },
+};
+
+
+
+const KEYS_FILE: &str = "keys.ron";
+
+const DEFAULT_BINDINGS: &[(&str, Command)] = &[
+    ("E", Command::Enclose),
+    ("S", Command::Sprout),
+    ("N", Command::Pass),
+    ("Ctrl+X", Command::Cut),
+    ("F2", Command::Rename),
+    ("Delete", Command::Delete),
+
+    ("Ctrl+S", Command::Save),
+    ("Ctrl+Shift+S", Command::SaveAs),
+    ("Ctrl+O", Command::Load),
+
+    ("Ctrl+Z", Command::Undo),
+    ("Ctrl+Shift+Z", Command::Redo),
+
+    ("Ctrl+A", Command::SelectAll),
+    ("Space", Command::SelectFocused),
+
+    ("Up", Command::FocusUp),
+    ("Down", Command::FocusDown),
+    ("Left", Command::FocusLeft),
+    ("Right", Command::FocusRight),
+    ("Ctrl+Down", Command::FocusIn),
+    ("Ctrl+Up", Command::FocusOut),
+
+    ("Plus", Command::ZoomIn),
+    ("Equals", Command::ZoomIn),
+    ("NumpadAdd", Command::ZoomIn),
+    ("Minus", Command::ZoomOut),
+    ("NumpadSubtract", Command::ZoomOut),
+    ("0", Command::FitToWindow),
+    ("F", Command::ZoomToSelection),
+
+    ("B", Command::ToggleBoundary),
+    ("F9", Command::ToggleDrawing),
+
+    ("Alt+0", Command::FocusLevel(0)),
+    ("Alt+1", Command::FocusLevel(1)),
+    ("Alt+2", Command::FocusLevel(2)),
+    ("Alt+3", Command::FocusLevel(3)),
+    ("Alt+4", Command::FocusLevel(4)),
+    ("Alt+5", Command::FocusLevel(5)),
+    ("Alt+6", Command::FocusLevel(6)),
+    ("Alt+7", Command::FocusLevel(7)),
+    ("Alt+8", Command::FocusLevel(8)),
+    ("Alt+9", Command::FocusLevel(9)),
+];
+
+/// Names of the keys which can be bound, as written in the config file.
+///
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::A), ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D), ("E", KeyCode::E),
+    ("F", KeyCode::F), ("G", KeyCode::G), ("H", KeyCode::H), ("I", KeyCode::I), ("J", KeyCode::J),
+    ("K", KeyCode::K), ("L", KeyCode::L), ("M", KeyCode::M), ("N", KeyCode::N), ("O", KeyCode::O),
+    ("P", KeyCode::P), ("Q", KeyCode::Q), ("R", KeyCode::R), ("S", KeyCode::S), ("T", KeyCode::T),
+    ("U", KeyCode::U), ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X), ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+
+    ("0", KeyCode::Key0), ("1", KeyCode::Key1), ("2", KeyCode::Key2), ("3", KeyCode::Key3), ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5), ("6", KeyCode::Key6), ("7", KeyCode::Key7), ("8", KeyCode::Key8), ("9", KeyCode::Key9),
+
+    ("F1", KeyCode::F1), ("F2", KeyCode::F2), ("F3", KeyCode::F3), ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5), ("F6", KeyCode::F6), ("F7", KeyCode::F7), ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9), ("F10", KeyCode::F10), ("F11", KeyCode::F11), ("F12", KeyCode::F12),
+
+    ("Up", KeyCode::Up), ("Down", KeyCode::Down), ("Left", KeyCode::Left), ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home), ("End", KeyCode::End), ("PageUp", KeyCode::PageUp), ("PageDown", KeyCode::PageDown),
+
+    ("Space", KeyCode::Space), ("Delete", KeyCode::Delete), ("Backspace", KeyCode::Back), ("Insert", KeyCode::Insert),
+
+    ("Plus", KeyCode::Plus), ("Minus", KeyCode::Minus), ("Equals", KeyCode::Equals),
+    ("NumpadAdd", KeyCode::Add), ("NumpadSubtract", KeyCode::Subtract),
+];
+
+
+
+/// Commands which can be bound to a key.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Command {
+    Enclose,
+    Sprout,
+    Pass,
+    Cut,
+    Rename,
+    Delete,
+    Ungroup,
+
+    Save,
+    SaveAs,
+    Load,
+    Export,
+    Script,
+    Replay,
+
+    Undo,
+    Redo,
+
+    SelectAll,
+    SelectFocused,
+
+    FocusUp,
+    FocusDown,
+    FocusLeft,
+    FocusRight,
+    FocusIn,
+    FocusOut,
+
+    ZoomIn,
+    ZoomOut,
+    FitToWindow,
+    ZoomToSelection,
+
+    ToggleLevel(usize),
+    FocusLevel(usize),
+    ToggleBoundary,
+    ToggleDrawing,
+}
+
+/// A key along with the modifiers held with it.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    key: KeyCode,
+
+    control: bool,
+    shift: bool,
+    alt: bool,
+    logo: bool,
+}
+
+/// The key bindings, read from `~/.eru/keys.ron`, which lists chords such as `"Ctrl+Shift+Z"`
+/// along with their commands. Keys which aren't listed keep their default bindings.
+///
+#[derive(Debug, Clone)]
+pub struct Keys {
+    bindings: HashMap<Chord, Command>,
+}
+
+#[derive(serde::Deserialize)]
+struct Config {
+    #[serde(default)]
+    bindings: Vec<(String, Command)>,
+}
+
+
+
+impl Keys {
+    /// Reads the key bindings from the config directory, if there are any.
+    ///
+    pub fn load() -> Result<Self, Error> {
+        let path = config_dir()?.join(KEYS_FILE);
+
+        let mut keys = Self::default();
+
+        if path.exists() {
+            let bound =
+            Self::parse(&workspace::read(&path)?)
+                .map_err(|reason| Error::Keys { file: path.clone(), reason })?;
+
+            keys.bindings.extend(bound);
+        }
+
+        Ok(keys)
+    }
+
+    /// Reads the bindings listed in the config file, where each chord may be bound only once.
+    ///
+    fn parse(source: &str) -> Result<HashMap<Chord, Command>, String> {
+        let Config { bindings } =
+        ron::from_str(source)
+            .map_err(|e| e.to_string())?;
+
+        let mut bound = HashMap::new();
+
+        for (chord, command) in bindings {
+            let parsed = Chord::parse(&chord)?;
+
+            if bound.insert(parsed, command).is_some() {
+                return Err(format!["`{}` is bound more than once", chord]);
+            }
+        }
+
+        Ok(bound)
+    }
+
+    /// The command bound to the key, if any.
+    ///
+    /// Holding shift also selects a range, so a chord without shift applies if the key isn't
+    /// bound with it.
+    ///
+    pub fn command(&self, key: KeyCode, modifiers: Modifiers) -> Option<Command> {
+        let chord = Chord::new(key, modifiers);
+
+        self.bindings
+            .get(&chord)
+            .or_else(|| self.bindings.get(&Chord { shift: false, ..chord }))
+            .copied()
+    }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        let bindings =
+        DEFAULT_BINDINGS
+            .iter()
+            .map(|(chord, command)| (Chord::parse(chord).unwrap(), *command))
+            .collect();
+
+        Self { bindings }
+    }
+}
+
+
+impl Chord {
+    fn new(key: KeyCode, modifiers: Modifiers) -> Self {
+        Self {
+            key,
+
+            control: modifiers.control,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+            logo: modifiers.logo,
+        }
+    }
+
+    fn parse(chord: &str) -> Result<Self, String> {
+        let mut parts: Vec<_> = chord.split('+').map(str::trim).collect();
+
+        // NOTE: `Ctrl++` binds the plus key.
+        //
+        if chord.ends_with("++") {
+            parts.truncate(parts.len() - 2);
+            parts.push("Plus");
+        }
+
+        let (key, modifiers) = parts.split_last().unwrap();
+
+        let key =
+        KEY_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, code)| *code)
+            .ok_or_else(|| format!["unknown key `{}` in `{}`", key, chord])?;
+
+        let mut this = Self {
+            key,
+
+            control: false,
+            shift: false,
+            alt: false,
+            logo: false,
+        };
+
+        for modifier in modifiers {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => this.control = true,
+                "shift" => this.shift = true,
+                "alt" => this.alt = true,
+                "logo" | "super" | "cmd" => this.logo = true,
+
+                _ => return Err(format!["unknown modifier `{}` in `{}`, expected Ctrl, Shift, Alt or Logo", modifier, chord]),
+            }
+        }
+
+        Ok(this)
+    }
+}
+
+
+impl Command {
+    /// Whether the command applies even while a pop-up is open.
+    ///
+    pub fn while_editing(self) -> bool {
+        matches![self, Self::Undo | Self::Redo]
+    }
+
+    pub fn message(self) -> GlobalMessage {
+        let tool = GlobalMessage::Sidebar;
+
+        match self {
+            Self::Enclose => tool(sidebar::Message::Enclose),
+            Self::Sprout => tool(sidebar::Message::Sprout),
+            Self::Pass => tool(sidebar::Message::Pass),
+            Self::Cut => tool(sidebar::Message::Cut),
+            Self::Rename => tool(sidebar::Message::Rename),
+            Self::Delete => tool(sidebar::Message::Delete),
+            Self::Ungroup => tool(sidebar::Message::Ungroup),
+
+            Self::Save => tool(sidebar::Message::Save),
+            Self::SaveAs => tool(sidebar::Message::SaveAs),
+            Self::Load => tool(sidebar::Message::Load),
+            Self::Export => tool(sidebar::Message::Export),
+            Self::Script => tool(sidebar::Message::Script),
+            Self::Replay => tool(sidebar::Message::Replay),
+
+            Self::Undo => tool(sidebar::Message::Undo),
+            Self::Redo => tool(sidebar::Message::Redo),
+
+            Self::SelectAll => GlobalMessage::SelectAll,
+            Self::SelectFocused => GlobalMessage::SelectFocused,
+
+            Self::FocusUp => GlobalMessage::Navigate(Direction::Up),
+            Self::FocusDown => GlobalMessage::Navigate(Direction::Down),
+            Self::FocusLeft => GlobalMessage::Navigate(Direction::Left),
+            Self::FocusRight => GlobalMessage::Navigate(Direction::Right),
+            Self::FocusIn => GlobalMessage::Navigate(Direction::In),
+            Self::FocusOut => GlobalMessage::Navigate(Direction::Out),
+
+            Self::ZoomIn => GlobalMessage::Viewport(viewport::Message::ZoomIn),
+            Self::ZoomOut => GlobalMessage::Viewport(viewport::Message::ZoomOut),
+            Self::FitToWindow => GlobalMessage::Viewport(viewport::Message::Fit),
+            Self::ZoomToSelection => GlobalMessage::Viewport(viewport::Message::ZoomToSelection),
+
+            Self::ToggleLevel(level) => GlobalMessage::Levels(level_bar::Message::Toggle(level)),
+            Self::FocusLevel(level) => GlobalMessage::Levels(level_bar::Message::Focus(level)),
+            Self::ToggleBoundary => GlobalMessage::Levels(level_bar::Message::ToggleBoundary),
+            Self::ToggleDrawing => GlobalMessage::Levels(level_bar::Message::ToggleDrawing),
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_default_binding_parses() {
+        for (chord, _) in DEFAULT_BINDINGS {
+            assert![Chord::parse(chord).is_ok(), "`{}` doesn't parse", chord];
+        }
+
+        assert_eq![Keys::default().bindings.len(), DEFAULT_BINDINGS.len()];
+    }
+
+    #[test]
+    fn modifiers_are_read_in_any_case() {
+        let redo = Chord { key: KeyCode::Z, control: true, shift: true, alt: false, logo: false };
+
+        assert_eq![Chord::parse("Ctrl+Shift+Z"), Ok(redo)];
+        assert_eq![Chord::parse("shift + control + z"), Ok(redo)];
+
+        assert_eq![Chord::parse("Ctrl++").map(|chord| chord.key), Ok(KeyCode::Plus)];
+    }
+
+    #[test]
+    fn malformed_chords_are_rejected() {
+        for chord in ["", "+", "Ctrl+", "Ctrl+Foo", "Hyper+A", "A+B"] {
+            assert![Chord::parse(chord).is_err(), "`{}` parses", chord];
+        }
+    }
+
+    #[test]
+    fn bindings_are_read_from_the_config() {
+        let bound = Keys::parse(r#"(bindings: [("Ctrl+Y", Redo), ("F5", Replay)])"#).unwrap();
+
+        assert_eq![bound.len(), 2];
+        assert_eq![bound.get(&Chord::parse("F5").unwrap()), Some(&Command::Replay)];
+    }
+
+    #[test]
+    fn duplicate_bindings_are_rejected() {
+        let duplicate = Keys::parse(r#"(bindings: [("Ctrl+Y", Redo), ("ctrl+y", Undo)])"#);
+
+        assert_eq![duplicate, Err("`ctrl+y` is bound more than once".to_string())];
+    }
+}
@@ -2,6 +2,7 @@ pub mod app;
 
 pub mod pop_up;
 pub mod general;
-pub mod opetope;
+pub mod workspace;
+pub mod keys;
 
 pub use app::App;
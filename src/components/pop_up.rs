@@ -38,6 +38,7 @@ enum Data<Msg> {
     Dialog { on_close: Msg, on_confirm: Msg },
 
     Error,
+    Notice,
 }
 pub struct Form<'s, Msg> {
     children: Vec<iced::Element<'s, Msg>>,
@@ -60,6 +61,13 @@ impl<'s, Msg> Form<'s, Msg> {
         }
     }
 
+    pub fn notice(message: impl Into<String>) -> Self {
+        Self {
+            children: vec![iced::Text::new(message).into()],
+            data: Data::Notice,
+        }
+    }
+
     pub fn push(mut self, child: impl Into<iced::Element<'s, Msg>>) -> Self {
         self.children.push(child.into());
         self
@@ -70,14 +78,12 @@ macro_rules! view_form {
     ( $view_fn:ident => $element:ident ~ $align:ident ) => {
         pub fn $view_fn(self, states: Option<(&'s mut iced::button::State, &'s mut iced::button::State)>) -> iced::Element<'s, Msg> {
             let mut children = self.children;
-            let mut is_error = true;
+            let is_error = matches![self.data, Data::Error];
 
             children.insert(0, iced::Space::with_width(iced::Length::Fill).into());
             children.push(iced::Space::with_width(iced::Length::Fill).into());
 
             if let Data::Dialog { on_close, on_confirm } = self.data {
-                is_error = false;
-
                 let (close, confirm) = states.unwrap();
 
                 children.insert(0, CloseButton::cross().on_press(on_close).view(close));
@@ -93,10 +99,10 @@ macro_rules! view_form {
             .width(iced::Length::Fill);
 
             if is_error {
-                pop_up.style(crate::styles::container::Error)
+                pop_up.style(eru::styles::container::Error)
 
             } else {
-                pop_up.style(crate::styles::container::PopUp)
+                pop_up.style(eru::styles::container::PopUp)
             }
             .into()
         }
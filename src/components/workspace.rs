@@ -0,0 +1,140 @@
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::components::app::Error;
+
+
+
+const CONFIG_DIR: &str = ".eru";
+const RECENT_FILE: &str = "recent.ron";
+
+const RECENT_COUNT: usize = 8;
+
+
+
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    file: Option<PathBuf>,
+    recent: Vec<PathBuf>,
+
+    pub dirty: bool,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Recent {
+    files: Vec<PathBuf>,
+}
+
+
+
+impl Workspace {
+    /// Creates an empty workspace, restoring the recent files from the config directory.
+    ///
+    pub fn load() -> Result<Self, Error> {
+        let path = config_dir()?.join(RECENT_FILE);
+
+        let recent =
+        if path.exists() {
+            let Recent { files } =
+            ron::from_str(&read(&path)?)
+                .map_err(|e| Error::Deserialization(format!["{}: {}", path.display(), e]))?;
+
+            files
+
+        } else {
+            vec![]
+        };
+
+        Ok(Self {
+            recent,
+
+            ..fill![]
+        })
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    pub fn recent(&self) -> &[PathBuf] {
+        &self.recent
+    }
+
+    pub fn title(&self) -> String {
+        let name =
+        self.file
+            .as_ref()
+            .and_then(|file| file.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_string());
+
+        let dirty = if self.dirty { "*" } else { "" };
+
+        format!["{}{} - eru", name, dirty]
+    }
+
+    /// Makes `path` the current file and remembers it in the recent files.
+    ///
+    pub fn set_file(&mut self, path: PathBuf) -> Result<(), Error> {
+        let path = path.canonicalize().unwrap_or(path);
+
+        self.recent.retain(|file| file != &path);
+        self.recent.insert(0, path.clone());
+        self.recent.truncate(RECENT_COUNT);
+
+        self.file = Some(path);
+        self.dirty = false;
+
+        self.store_recent()
+    }
+
+    fn store_recent(&self) -> Result<(), Error> {
+        let dir = config_dir()?;
+
+        fs::create_dir_all(&dir)
+            .map_err(|e| Error::PathError(format!["{}: {}", dir.display(), e]))?;
+
+        let recent = Recent { files: self.recent.clone() };
+
+        let contents =
+        ron::ser::to_string_pretty(&recent, fill![])
+            .map_err(|e| Error::PathError(e.to_string()))?;
+
+        write(&dir.join(RECENT_FILE), &contents)
+    }
+}
+
+
+pub fn config_dir() -> Result<PathBuf, Error> {
+    home::home_dir()
+        .map(|home| home.join(CONFIG_DIR))
+        .ok_or(Error::NoHomeDir)
+}
+
+/// Interprets a path entered by the user, expanding a leading `~` to the home directory.
+///
+pub fn expand(path: &str) -> Result<PathBuf, Error> {
+    let path = path.trim();
+
+    if path.is_empty() {
+        Err(Error::PathError("no file path given".to_string()))
+
+    } else if path == "~" || path.starts_with("~/") {
+        let home = home::home_dir().ok_or(Error::NoHomeDir)?;
+
+        Ok(home.join(path.trim_start_matches('~').trim_start_matches('/')))
+
+    } else {
+        Ok(PathBuf::from(path))
+    }
+}
+
+pub fn read(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path)
+        .map_err(|e| Error::PathError(format!["{}: {}", path.display(), e]))
+}
+
+pub fn write(path: &Path, contents: &str) -> Result<(), Error> {
+    fs::write(path, contents)
+        .map_err(|e| Error::PathError(format!["{}: {}", path.display(), e]))
+}
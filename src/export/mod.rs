@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use crate::opetope::{ Diagram, format::Named };
+use crate::styles::container::color::rgb;
+
+pub mod svg;
+pub mod tikz;
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Svg,
+    Tikz,
+}
+
+/// Colors of an exported picture, as RGB.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Palette {
+    pub line: [u8; 3],
+    pub border: [u8; 3],
+    pub background: [u8; 3],
+    pub text: [u8; 3],
+}
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            line: rgb::LINE,
+            border: rgb::BORDER,
+            background: rgb::BACKGROUND,
+            text: rgb::TEXT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnknownFormat(Option<String>),
+}
+
+
+
+impl Format {
+    /// Guesses the format from the extension of `path`.
+    ///
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let extension =
+        path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+
+        match extension.as_deref() {
+            Some("svg") => Ok(Self::Svg),
+            Some("tex") | Some("tikz") => Ok(Self::Tikz),
+
+            _ => Err(Error::UnknownFormat(extension)),
+        }
+    }
+}
+
+impl Palette {
+    accessors! {
+        line: [u8; 3],
+        border: [u8; 3],
+        background: [u8; 3],
+        text: [u8; 3],
+    }
+}
+
+/// Renders the opetope with all its levels, in the colors of `palette`.
+///
+pub fn export<Data: Clone + Named>(diagram: &Diagram<Data>, format: Format, palette: &Palette) -> String {
+    match format {
+        Format::Svg => svg::render(diagram, palette),
+        Format::Tikz => tikz::render(diagram, palette),
+    }
+}
+
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat(Some(ext)) =>
+                write![fmt, "Cannot export to `.{}` files, use `.svg` or `.tex`", ext],
+
+            Self::UnknownFormat(None) =>
+                write![fmt, "Missing file extension, use `.svg` or `.tex`"],
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::opetope::{
+    Diagram,
+    format::Named,
+    layout::{ Layout, Shape, Rect },
+};
+
+use crate::styles::container::{ PADDING, cell };
+
+use super::Palette;
+
+
+
+pub const FONT_FAMILY: &str = "DejaVu Sans, sans-serif";
+pub const FONT_SIZE: u16 = crate::styles::metrics::LABEL_SIZE;
+
+
+
+/// Draws the opetope as it is laid out on screen.
+///
+pub fn render<Data: Clone + Named>(diagram: &Diagram<Data>, palette: &Palette) -> String {
+    from_layout(&diagram.layout(), palette)
+}
+
+pub fn from_layout(layout: &Layout, palette: &Palette) -> String {
+    let margin = PADDING as f32;
+
+    let width = layout.width + 2. * margin;
+    let height = layout.height + 2. * margin;
+
+    let mut svg = vec![
+        format![
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height,
+        ],
+        format![
+            "<style>.cell {{ fill: {}; stroke: {}; stroke-width: {}; }} .line {{ fill: {}; }} text {{ fill: {}; font-family: {}; font-size: {}px; text-anchor: middle; dominant-baseline: central; }}</style>",
+            hex(palette.background),
+            hex(palette.border),
+            cell::WIDTH,
+            hex(palette.line),
+            hex(palette.text),
+            FONT_FAMILY,
+            FONT_SIZE,
+        ],
+        format![r#"<g transform="translate({m} {m})">"#, m = margin],
+    ];
+
+    for shape in &layout.shapes {
+        match shape {
+            Shape::Line(Rect { x, y, width, height }) =>
+                svg.push(format![
+                    r#"<rect class="line" x="{}" y="{}" width="{}" height="{}"/>"#,
+                    x, y, width, height,
+                ]),
+
+            Shape::Cell { label, bounds, data, .. } => {
+                // NOTE: The border is drawn inside the bounds, like in iced.
+                //
+                let inset = cell::WIDTH / 2.;
+
+                svg.push(format![
+                    r#"<rect class="cell" x="{}" y="{}" width="{}" height="{}" rx="{}"/>"#,
+                    bounds.x + inset,
+                    bounds.y + inset,
+                    bounds.width - cell::WIDTH,
+                    bounds.height - cell::WIDTH,
+                    cell::RADIUS,
+                ]);
+
+                let (x, y) = data.center();
+
+                svg.push(format![r#"<text x="{}" y="{}">{}</text>"#, x, y, escape(label)]);
+            },
+        }
+    }
+
+    svg.push("</g>".to_string());
+    svg.push("</svg>".to_string());
+
+    svg.join("\n")
+}
+
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!["#{:02x}{:02x}{:02x}", r, g, b]
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c|
+            match c {
+                '&' => "&amp;".to_string(),
+                '<' => "&lt;".to_string(),
+                '>' => "&gt;".to_string(),
+                '"' => "&quot;".to_string(),
+
+                c => c.to_string(),
+            }
+        )
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::testing::*;
+
+    #[test]
+    fn picture_matches_the_layout() {
+        let diagram = opetope("extrude 0 as a wrap α");
+        let layout = diagram.layout();
+
+        let svg = render(&diagram, &Palette::default());
+
+        let margin = 2 * PADDING;
+
+        assert![svg.starts_with(&format![
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = 120 + margin,
+            h = 92 + margin,
+        ])];
+
+        // NOTE: The cell `α` is laid out at (90, 22), 30 by 38 units large.
+        //
+        let inset = cell::WIDTH / 2.;
+
+        assert![svg.contains(&format![
+            r#"<rect class="cell" x="{}" y="{}" width="{}" height="{}" rx="{}"/>"#,
+            90. + inset,
+            22. + inset,
+            30. - cell::WIDTH,
+            38. - cell::WIDTH,
+            cell::RADIUS,
+        ])];
+        assert![svg.contains(r#"<text x="105" y="41">α</text>"#)];
+
+        let count = |pattern: &str| svg.matches(pattern).count();
+
+        assert_eq![count("<text "), 3];
+        assert_eq![count(r#"<rect class="cell""#), 3];
+        assert_eq![count(r#"<rect class="line""#), layout.shapes.len() - 3];
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        assert_eq![escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;"];
+    }
+}
@@ -0,0 +1,213 @@
+use crate::opetope::{
+    Diagram,
+    format::Named,
+    layout::{ Layout, Shape, Rect },
+};
+
+use crate::styles::container::cell;
+
+use super::Palette;
+
+
+
+/// Length of a layout unit (a pixel on screen) in points, chosen so that the labels fit the
+/// normal text size of a document.
+///
+pub const UNIT: f32 = 0.5;
+
+
+
+/// Writes a standalone LaTeX document drawing the opetope as it is laid out on screen.
+///
+pub fn render<Data: Clone + Named>(diagram: &Diagram<Data>, palette: &Palette) -> String {
+    let picture = from_layout(&diagram.layout(), palette);
+
+    [
+        "\\documentclass[tikz]{standalone}",
+        "\\usepackage[utf8]{inputenc}",
+        "\\begin{document}",
+        &picture,
+        "\\end{document}",
+    ].join("\n")
+}
+
+/// Writes only the `tikzpicture` environment, to be included in another document.
+///
+pub fn from_layout(layout: &Layout, palette: &Palette) -> String {
+    let mut tikz = vec![
+        format!["\\begin{{tikzpicture}}[x={u}pt, y=-{u}pt]", u = UNIT],
+
+        format!["\\definecolor{{eruline}}{{RGB}}{{{}}}", rgb(palette.line)],
+        format!["\\definecolor{{eruborder}}{{RGB}}{{{}}}", rgb(palette.border)],
+        format!["\\definecolor{{erubackground}}{{RGB}}{{{}}}", rgb(palette.background)],
+        format!["\\definecolor{{erutext}}{{RGB}}{{{}}}", rgb(palette.text)],
+    ];
+
+    for shape in &layout.shapes {
+        match shape {
+            Shape::Line(rect) =>
+                tikz.push(format!["  \\fill[eruline] {};", rectangle(rect, 0.)]),
+
+            Shape::Cell { label, bounds, data, .. } => {
+                tikz.push(format![
+                    "  \\filldraw[draw=eruborder, fill=erubackground, line width={}pt, rounded corners={}pt] {};",
+                    cell::WIDTH * UNIT,
+                    cell::RADIUS * UNIT,
+                    // NOTE: The border is drawn inside the bounds, like in iced.
+                    rectangle(bounds, cell::WIDTH / 2.),
+                ]);
+
+                let (x, y) = data.center();
+
+                tikz.push(format!["  \\node[text=erutext, font=\\sffamily] at ({}, {}) {{{}}};", x, y, escape(label)]);
+            },
+        }
+    }
+
+    tikz.push("\\end{tikzpicture}".to_string());
+
+    tikz.join("\n")
+}
+
+
+fn rectangle(rect: &Rect, inset: f32) -> String {
+    format![
+        "({}, {}) rectangle ({}, {})",
+        rect.x + inset,
+        rect.y + inset,
+        rect.x + rect.width - inset,
+        rect.y + rect.height - inset,
+    ]
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> String {
+    format!["{},{},{}", r, g, b]
+}
+
+/// Escapes the special characters of TeX, and writes Greek letters as math symbols, since the
+/// text fonts of LaTeX have none.
+///
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c|
+            match c {
+                '\\' => "\\textbackslash{}".to_string(),
+                '~' => "\\textasciitilde{}".to_string(),
+                '^' => "\\textasciicircum{}".to_string(),
+
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => format!["\\{}", c],
+
+                c =>
+                    match greek(c) {
+                        Some(symbol) if symbol.len() == 1 => symbol.to_string(),
+                        Some(symbol) => format!["\\ensuremath{{\\{}}}", symbol],
+
+                        None => c.to_string(),
+                    },
+            }
+        )
+        .collect()
+}
+
+/// The LaTeX symbol of a Greek letter. Capitals without a symbol of their own, which look like
+/// Latin ones, are written as the Latin letter.
+///
+fn greek(c: char) -> Option<&'static str> {
+    let symbol =
+    match c {
+        'α' => "alpha",
+        'β' => "beta",
+        'γ' => "gamma",
+        'δ' => "delta",
+        'ε' => "varepsilon",
+        'ζ' => "zeta",
+        'η' => "eta",
+        'θ' => "theta",
+        'ι' => "iota",
+        'κ' => "kappa",
+        'λ' => "lambda",
+        'μ' => "mu",
+        'ν' => "nu",
+        'ξ' => "xi",
+        'ο' => "o",
+        'π' => "pi",
+        'ρ' => "rho",
+        'ς' => "varsigma",
+        'σ' => "sigma",
+        'τ' => "tau",
+        'υ' => "upsilon",
+        'φ' => "varphi",
+        'χ' => "chi",
+        'ψ' => "psi",
+        'ω' => "omega",
+
+        'Γ' => "Gamma",
+        'Δ' => "Delta",
+        'Θ' => "Theta",
+        'Λ' => "Lambda",
+        'Ξ' => "Xi",
+        'Π' => "Pi",
+        'Σ' => "Sigma",
+        'Υ' => "Upsilon",
+        'Φ' => "Phi",
+        'Ψ' => "Psi",
+        'Ω' => "Omega",
+
+        'Α' => "A",
+        'Β' => "B",
+        'Ε' => "E",
+        'Ζ' => "Z",
+        'Η' => "H",
+        'Ι' => "I",
+        'Κ' => "K",
+        'Μ' => "M",
+        'Ν' => "N",
+        'Ο' => "O",
+        'Ρ' => "P",
+        'Τ' => "T",
+        'Χ' => "X",
+
+        _ => return None,
+    };
+
+    Some(symbol)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::testing::*;
+
+    #[test]
+    fn specials_are_escaped() {
+        assert_eq![escape("a_1 & 50%"), "a\\_1 \\& 50\\%"];
+        assert_eq![escape("{x}^\\"), "\\{x\\}\\textasciicircum{}\\textbackslash{}"];
+    }
+
+    #[test]
+    fn greek_letters_are_written_as_symbols() {
+        assert_eq![escape("αθ"), "\\ensuremath{\\alpha}\\ensuremath{\\theta}"];
+        assert_eq![escape("Ωο"), "\\ensuremath{\\Omega}o"];
+        assert_eq![escape("Α1"), "A1"];
+    }
+
+    #[test]
+    fn document_draws_every_cell() {
+        let diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β");
+
+        let tex = render(&diagram, &Palette::default());
+        let lines: Vec<_> = tex.lines().collect();
+
+        assert_eq![lines[.. 3], ["\\documentclass[tikz]{standalone}", "\\usepackage[utf8]{inputenc}", "\\begin{document}"]];
+        assert_eq![lines[lines.len() - 2 ..], ["\\end{tikzpicture}", "\\end{document}"]];
+
+        assert![tex.is_ascii()];
+
+        assert_eq![tex.matches("\\node").count(), 5];
+        assert_eq![tex.matches("\\filldraw").count(), 5];
+
+        assert![tex.contains("{\\ensuremath{\\alpha}};")];
+        assert![tex.contains("{\\ensuremath{\\beta}};")];
+    }
+}
@@ -0,0 +1,122 @@
+use std::hash::Hash;
+
+use iced_native::{
+    event, layout, mouse,
+    Clipboard, Element, Event, Hasher, Layout, Length, Point, Rectangle, Widget,
+};
+
+
+
+/// Reports when the cursor enters or leaves a cell. Of nested cells, only the innermost one under
+/// the cursor is hovered.
+///
+pub struct Hover<'a, Message, Renderer> {
+    content: Element<'a, Message, Renderer>,
+
+    hovered: bool,
+
+    on_enter: Message,
+    on_leave: Message,
+}
+
+
+
+impl<'a, Message, Renderer> Hover<'a, Message, Renderer> {
+    /// Wraps a cell, where `hovered` tells whether the cell was hovered when last updated, so
+    /// that the messages are only sent on a change.
+    ///
+    pub fn new(
+        content: impl Into<Element<'a, Message, Renderer>>,
+        hovered: bool,
+        on_enter: Message,
+        on_leave: Message,
+    ) -> Self {
+
+        Self {
+            content: content.into(),
+
+            hovered,
+
+            on_enter,
+            on_leave,
+        }
+    }
+}
+
+impl<'a, Message: Clone, Renderer: iced_native::Renderer> Widget<Message, Renderer> for Hover<'a, Message, Renderer> {
+    fn width(&self) -> Length {
+        self.content.width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+
+        self.content.draw(renderer, defaults, layout, cursor_position, viewport)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.content.hash_layout(state);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        messages: &mut Vec<Message>,
+    ) -> event::Status {
+
+        let status = self.content.on_event(event.clone(), layout, cursor_position, renderer, clipboard, messages);
+
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                // NOTE: A nested cell under the cursor captures the movement, so that the groups
+                // around it aren't hovered.
+                //
+                let inner = matches![status, event::Status::Captured];
+                let here = !inner && layout.bounds().contains(cursor_position);
+
+                if here && !self.hovered {
+                    messages.push(self.on_enter.clone());
+
+                } else if !here && self.hovered {
+                    messages.push(self.on_leave.clone());
+                }
+
+                if here || inner {
+                    event::Status::Captured
+
+                } else {
+                    status
+                }
+            },
+
+            _ => status,
+        }
+    }
+}
+
+impl<'a, Message: Clone + 'a, Renderer: iced_native::Renderer + 'a> From<Hover<'a, Message, Renderer>> for Element<'a, Message, Renderer> {
+    fn from(hover: Hover<'a, Message, Renderer>) -> Self {
+        Element::new(hover)
+    }
+}
@@ -0,0 +1,12 @@
+#[macro_use]
+pub mod utils;
+pub mod styles;
+#[cfg(feature = "gui")]
+pub mod behavior;
+#[cfg(feature = "gui")]
+pub mod hover;
+
+pub mod model;
+pub mod opetope;
+
+pub mod export;
@@ -1,10 +1,7 @@
 #[macro_use]
-mod utils;
-mod styles;
-mod behavior;
-mod components;
+extern crate eru;
 
-mod model;
+mod components;
 
 
 use iced::Application;
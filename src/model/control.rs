@@ -5,3 +5,16 @@ pub enum Render {
 
     Static,
 }
+
+/// How the opetope is drawn on screen.
+///
+#[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Drawing {
+    /// On a single canvas, from the headless layout.
+    ///
+    #[default]
+    Canvas,
+    /// As nested widgets, with a button for each cell.
+    ///
+    Widgets,
+}
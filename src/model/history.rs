@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+
+
+pub const DEPTH: usize = 64;
+
+
+/// Bounded undo/redo stack of whole-state snapshots.
+///
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    depth: usize,
+
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new(DEPTH)
+    }
+}
+
+
+impl<T> History<T> {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+
+            undo: VecDeque::with_capacity(depth),
+            redo: vec![],
+        }
+    }
+
+    /// Remembers the state preceding an edit. Invalidates the redo stack.
+    ///
+    pub fn record(&mut self, before: T) {
+        self.redo.clear();
+
+        self.undo.push_back(before);
+
+        if self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Swaps `current` with the last recorded state. Returns `false` if there is nothing to undo.
+    ///
+    pub fn undo(&mut self, current: &mut T) -> bool {
+        if let Some(before) = self.undo.pop_back() {
+            self.redo.push(std::mem::replace(current, before));
+
+            true
+
+        } else {
+            false
+        }
+    }
+
+    /// Swaps `current` with the last undone state. Returns `false` if there is nothing to redo.
+    ///
+    pub fn redo(&mut self, current: &mut T) -> bool {
+        if let Some(after) = self.redo.pop() {
+            self.undo.push_back(std::mem::replace(current, after));
+
+            true
+
+        } else {
+            false
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A history of the states `0 .. n`, with the state `n` current.
+    ///
+    fn recorded(n: u32) -> (History<u32>, u32) {
+        let mut history = History::default();
+
+        for state in 0 .. n {
+            history.record(state);
+        }
+
+        (history, n)
+    }
+
+
+    #[test]
+    fn undo_restores_the_recorded_states() {
+        let (mut history, mut current) = recorded(3);
+
+        assert![history.undo(&mut current)];
+        assert_eq![current, 2];
+
+        assert![history.undo(&mut current)];
+        assert![history.undo(&mut current)];
+        assert_eq![current, 0];
+    }
+
+    #[test]
+    fn undo_past_the_start_keeps_the_state() {
+        let (mut history, mut current) = recorded(1);
+
+        assert![history.undo(&mut current)];
+
+        assert![!history.can_undo()];
+        assert![!history.undo(&mut current)];
+        assert_eq![current, 0];
+
+        assert![history.redo(&mut current)];
+        assert_eq![current, 1];
+    }
+
+    #[test]
+    fn redo_reapplies_the_undone_states() {
+        let (mut history, mut current) = recorded(3);
+
+        history.undo(&mut current);
+        history.undo(&mut current);
+
+        assert![history.redo(&mut current)];
+        assert_eq![current, 2];
+
+        assert![history.redo(&mut current)];
+        assert_eq![current, 3];
+
+        assert![!history.can_redo()];
+        assert![!history.redo(&mut current)];
+        assert_eq![current, 3];
+    }
+
+    #[test]
+    fn new_edit_drops_the_undone_states() {
+        let (mut history, mut current) = recorded(3);
+
+        history.undo(&mut current);
+        history.record(current);
+        current = 7;
+
+        assert![!history.can_redo()];
+        assert![!history.redo(&mut current)];
+
+        assert![history.undo(&mut current)];
+        assert_eq![current, 2];
+        assert![history.undo(&mut current)];
+        assert_eq![current, 1];
+    }
+
+    #[test]
+    fn oldest_states_are_forgotten() {
+        let mut history = History::new(2);
+        let mut current = 3;
+
+        for state in 0 .. 3 {
+            history.record(state);
+        }
+
+        assert![history.undo(&mut current)];
+        assert![history.undo(&mut current)];
+        assert![!history.undo(&mut current)];
+        assert_eq![current, 1];
+    }
+}
@@ -0,0 +1,94 @@
+/// How a level of the opetope is drawn.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Fold {
+    #[default]
+    Shown,
+
+    /// Drawn small and without interaction, see `zoom::THUMBNAIL`.
+    ///
+    Thumbnail,
+    Hidden,
+}
+
+/// Which levels of the opetope are drawn, and how. The tower is level 0.
+///
+/// While a level is focused, it is the only one drawn at full size and the others which are
+/// shown are drawn as thumbnails.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Levels {
+    folds: Vec<Fold>,
+    focus: Option<usize>,
+}
+
+impl Fold {
+    /// The fold following this one when toggled.
+    ///
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Shown => Self::Thumbnail,
+            Self::Thumbnail => Self::Hidden,
+            Self::Hidden => Self::Shown,
+        }
+    }
+}
+
+
+impl Levels {
+    /// How the level is drawn, taking the focused level into account.
+    ///
+    pub fn fold(&self, level: usize) -> Fold {
+        let fold = self.set_fold(level);
+
+        match self.focus {
+            Some(focus) if focus == level => Fold::Shown,
+            Some(_) if fold == Fold::Shown => Fold::Thumbnail,
+
+            _ => fold,
+        }
+    }
+
+    /// How the level is drawn when no level is focused.
+    ///
+    pub fn set_fold(&self, level: usize) -> Fold {
+        self.folds
+            .get(level)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn toggle(&mut self, level: usize) {
+        if self.folds.len() <= level {
+            self.folds.resize(level + 1, Fold::Shown);
+        }
+
+        self.folds[level] = self.folds[level].next();
+    }
+
+    /// Focuses the level, or shows all levels again if it's already focused.
+    ///
+    pub fn toggle_focus(&mut self, level: usize) {
+        self.focus =
+        if self.focus == Some(level) {
+            None
+
+        } else {
+            Some(level)
+        };
+    }
+
+    pub const fn focused(&self) -> Option<usize> {
+        self.focus
+    }
+
+    /// Forgets about the levels above `top`, as when an opetope with fewer levels is loaded.
+    ///
+    pub fn fit(&mut self, top: usize) {
+        self.folds.truncate(top + 1);
+
+        if matches![self.focus, Some(focus) if focus > top] {
+            self.focus = None;
+        }
+    }
+}
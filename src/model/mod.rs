@@ -1,5 +1,13 @@
+#[cfg(feature = "gui")]
 mod icon;
 mod control;
+mod history;
+pub mod zoom;
+pub mod levels;
 
+#[cfg(feature = "gui")]
 pub use icon::Icon;
-pub use control::Render;
+pub use control::{ Render, Drawing };
+pub use history::History;
+pub use zoom::Zoom;
+pub use levels::{ Levels, Fold };
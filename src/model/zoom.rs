@@ -0,0 +1,52 @@
+pub const MIN: f32 = 0.1;
+pub const MAX: f32 = 4.;
+
+/// Factor by which a single zoom step scales the opetope.
+///
+pub const STEP: f32 = 1.25;
+
+/// Scale of a collapsed level, relative to the rest of the opetope.
+///
+pub const THUMBNAIL: f32 = 0.3;
+
+
+/// Scale at which the opetope is drawn.
+///
+/// Sizes are computed at the natural scale and only scaled when turned into widgets, so that the
+/// diagram keeps its proportions at any zoom.
+///
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Zoom(f32);
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+
+impl Zoom {
+    pub fn new(factor: f32) -> Self {
+        Self(factor.clamp(MIN, MAX))
+    }
+
+    pub const fn factor(self) -> f32 {
+        self.0
+    }
+
+    pub fn scale(self, length: u16) -> u16 {
+        (length as f32 * self.0).round() as u16
+    }
+
+    pub fn zoomed_in(self) -> Self {
+        Self::new(self.0 * STEP)
+    }
+
+    pub fn zoomed_out(self) -> Self {
+        Self::new(self.0 / STEP)
+    }
+
+    pub fn thumbnail(self) -> Self {
+        Self::new(self.0 * THUMBNAIL)
+    }
+}
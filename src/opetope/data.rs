@@ -0,0 +1,256 @@
+use std::ops;
+
+#[cfg(feature = "gui")]
+use iced::button;
+
+#[cfg(feature = "gui")]
+use crate::model::{ Render, Zoom };
+#[cfg(feature = "gui")]
+use crate::behavior::{ Clickable, SimpleView };
+
+#[cfg(feature = "gui")]
+use super::viewing::{ Message, ViewIndex };
+#[cfg(feature = "gui")]
+use crate::hover::Hover;
+
+
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Selectable<Data> {
+    val: Data,
+
+    selected: bool,
+
+    #[serde(skip)]
+    focused: bool,
+
+    #[serde(skip)]
+    highlight: Option<Highlight>,
+    #[serde(skip)]
+    hovered: bool,
+
+    #[cfg(feature = "gui")]
+    #[serde(skip)]
+    select: button::State,
+}
+
+/// The part a cell plays in the face of a hovered or selected cell.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Highlight {
+    End,
+    Fill,
+
+    /// In the face of the face, and so on down to the tower.
+    ///
+    Boundary,
+}
+
+
+
+const HEIGHT: u16 = 8;
+#[cfg(feature = "gui")]
+use crate::styles::container::PADDING;
+use crate::styles::container::cell::SPACING;
+
+/// Height of the label of a cell.
+///
+pub const DATA_HEIGHT: u16 = HEIGHT + 2 * SPACING;
+
+
+impl<Data> Selectable<Data> {
+    pub const fn inner(&self) -> &Data {
+        &self.val
+    }
+
+    /// Toggles the selection, as clicking the cell does.
+    ///
+    pub fn select(&mut self) {
+        self.selected = !self.selected;
+    }
+
+    pub fn set_selected(&mut self, selected: bool) {
+        self.selected = selected;
+    }
+
+    pub fn unselect(&mut self) {
+        self.selected = false;
+    }
+
+    pub const fn selected(&self) -> bool {
+        self.selected
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+    }
+
+    pub const fn focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn highlight(&mut self, highlight: Highlight) {
+        self.highlight = Some(highlight);
+    }
+
+    pub fn hover(&mut self) {
+        self.hovered = true;
+    }
+
+    /// Clears both the highlight and the hover.
+    ///
+    pub fn unhighlight(&mut self) {
+        self.highlight = None;
+        self.hovered = false;
+    }
+
+    pub const fn highlighted(&self) -> Option<Highlight> {
+        self.highlight
+    }
+
+    pub const fn hovered(&self) -> bool {
+        self.hovered
+    }
+}
+
+#[cfg(feature = "gui")]
+impl<Data: SimpleView> Selectable<Data> {
+    pub fn view_cell<'s>(
+        &'s mut self,
+        index: ViewIndex,
+        content_width: u16,
+        contents: Option<iced::Element<'s, Message>>,
+        render: Render,
+        zoom: Zoom,
+    ) -> ((u16, u16), iced::Element<'s, Message>) {
+
+        let (width, data) = self.view_data(content_width, zoom);
+
+        let contents =
+            if let Some(contents) = contents {
+                iced::Column::new()
+                    .push(contents)
+                    .push(data)
+                    .align_items(iced::Align::Center)
+                    .into()
+
+            } else {
+                data
+            };
+
+        let mut style =
+        match (self.selected, self.focused) {
+            (false, false) => crate::styles::container::CELL,
+            (true, false) => crate::styles::container::SELECTED_CELL,
+
+            (false, true) => crate::styles::container::FOCUSED_CELL,
+            (true, true) => crate::styles::container::FOCUSED_SELECTED_CELL,
+        };
+
+        if let Some(highlight) = self.highlight {
+            style = style.highlighted(highlight.color());
+        }
+
+        let hovered = self.hovered;
+
+        let cell =
+        iced::Button::new(self.state(), contents)
+            .style(style)
+            .padding(0);
+
+        let cell =
+        if render == Render::Interactive {
+            Hover::new(
+                cell.on_press(Message::Select(index.clone())),
+                hovered,
+                Message::Hover(index.clone()),
+                Message::Unhover(index),
+            ).into()
+
+        } else {
+            cell.into()
+        };
+
+        ((DATA_HEIGHT, width), cell)
+    }
+
+    /// The label of the cell, along with its width at the natural scale.
+    ///
+    pub fn view_data(&self, min_width: u16, zoom: Zoom) -> (u16, iced::Element<'static, Message>) {
+        let val = self.val.view_zoomed(zoom).1;
+
+        let width = self.width().max(min_width);
+
+        (
+            width,
+            iced::Element::from(
+                iced::Container::new(val)
+                .align_x(iced::Align::Center)
+                .width(zoom.scale(width).into())
+                .padding(zoom.scale(PADDING))
+            ).map(|_| Message::Idle),
+        )
+    }
+
+    pub fn width(&self) -> u16 {
+        let width = self.val.view().0;
+
+        width + 2 * PADDING
+    }
+}
+
+impl<Data> From<Data> for Selectable<Data> {
+    fn from(val: Data) -> Self {
+        Self {
+            val,
+
+            selected: false,
+            focused: false,
+            highlight: None,
+            hovered: false,
+
+            #[cfg(feature = "gui")]
+            select: fill![],
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Highlight {
+    pub const fn color(self) -> iced::Color {
+        match self {
+            Self::End => crate::styles::container::color::END,
+            Self::Fill => crate::styles::container::color::FILL,
+            Self::Boundary => crate::styles::container::color::BOUNDARY,
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl<Data: SimpleView> SimpleView for Selectable<Data> {
+    fn view(&self) -> (u16, iced::Element<'static, ()>) {
+        self.val.view()
+    }
+
+    fn view_zoomed(&self, zoom: Zoom) -> (u16, iced::Element<'static, ()>) {
+        self.val.view_zoomed(zoom)
+    }
+}
+#[cfg(feature = "gui")]
+impl<Data> Clickable for Selectable<Data> {
+    fn state(&mut self) -> &mut button::State {
+        &mut self.select
+    }
+}
+
+impl<Data> ops::Deref for Selectable<Data> {
+    type Target = Data;
+
+    fn deref(&self) -> &Self::Target {
+        &self.val
+    }
+}
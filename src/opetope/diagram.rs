@@ -0,0 +1,2213 @@
+use std::collections::{ HashMap, HashSet, VecDeque };
+
+use itertools::Itertools;
+use crate::utils::{ EncapsulateIter, ProjectIter };
+
+use serde::{ Serialize, Deserialize };
+use super::{ *, viewing::{ ViewIndex, Selection, Index } };
+use super::{
+    format::Named,
+    layout::{ self, Layout },
+    tree::{ TracingTree, Handle, NodeRef },
+    utils::{ CellCoordinator, LINE_LEN },
+};
+use crate::styles::container::cell::SPACING;
+use crate::model::{ Levels, Fold };
+
+
+
+macro_rules! interaction {
+    ( $name:ident ( &mut $self:ident, $cell:ident : &$sel_ty:ty $(, $arg:ident : $arg_ty:ty)* $(,)? ) in prev $prev_body:block in self $this_body:block ) => {
+        pub fn $name(&mut $self, $cell: &$sel_ty $(, $arg: $arg_ty)* ) -> EditResult<Interaction, Data> {
+            if $self.level() > $cell.level() + 1 {
+                let mut copy = $self.deep_copy($cell.level() + 1).unwrap();
+
+                let result = copy.$name($cell $(, $arg)*);
+
+                match result {
+                    EditResult::Ok(result) => EditResult::OkCopied { result, copy },
+
+                    EditResult::OkCopied { .. } => unreachable![],
+
+                    e => e,
+                }
+
+            } else if $self.level() < $cell.level() {
+                EditResult::Err(Error::TooMuchDepth($cell.level()))
+
+            } else if $self.level() == $cell.level()
+                $this_body
+
+            else
+                $prev_body
+        }
+    };
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Face {
+    pub ends: Vec<ViewIndex>,
+    pub fill: ViewIndex,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetaCell<Data> {
+    pub data: Data,
+    pub face: Face,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagram<Data> {
+    cells: TracingTree<MetaCell<Data>>,
+
+    prev: Tail<Data>,
+}
+
+
+/// Cells removed from a level, along with what the level above has to do about them.
+///
+pub(in super) struct Removal {
+    /// The removed cells, which are lines of the level above.
+    ///
+    pub(in super) cells: Vec<ViewIndex>,
+
+    /// Faces of the cells above which only wrap the removed cells, these are removed as well. Any
+    /// other use of the removed lines prevents the removal.
+    ///
+    pub(in super) wraps: Vec<Face>,
+
+    /// Lines which remain, but at another path.
+    ///
+    pub(in super) moved: Vec<(ViewIndex, ViewIndex)>,
+}
+
+
+
+// IMPL: Initialization
+//
+impl<Data> Diagram<Data> {
+    pub fn new(tail: Tail<Data>) -> Result<Self, Error> {
+        Ok(Self {
+            prev: tail,
+            cells: fill![],
+        })
+    }
+
+    pub(in super) fn from_parts(prev: Tail<Data>, cells: TracingTree<MetaCell<Data>>) -> Self {
+        Self {
+            prev,
+            cells,
+        }
+    }
+}
+
+// IMPL: Accessing
+//
+impl<Data> Diagram<Data> {
+    pub fn level(&self) -> usize {
+        self.prev.level() + 1
+    }
+
+    pub fn prev(&self) -> &Tail<Data> {
+        &self.prev
+    }
+
+    pub(in super) fn cells(&self) -> &TracingTree<MetaCell<Data>> {
+        &self.cells
+    }
+
+    pub(in super) fn get(&self, path: &[TimelessIndex]) -> Option<&MetaCell<Data>> {
+        self.cells.at(path)
+    }
+
+    pub(in super) fn get_mut(&mut self, path: &[TimelessIndex]) -> Option<&mut MetaCell<Data>> {
+        self.cells.at_mut(path)
+    }
+
+    /// Indices of all cells of this level, each group preceding its contents.
+    ///
+    pub fn indices(&self) -> Vec<ViewIndex> {
+        self.cells
+            .paths()
+            .into_iter()
+            .map(|(path, _)| self.to_index(path))
+            .collect()
+    }
+
+    pub fn iter_groups(&self) -> IterGroups<'_, Data> {
+        IterGroups {
+            level: self.level() - 1,
+            cells: self
+                .cells
+                .nodes()
+                .into_iter()
+                .map(|(index, node)| (vec![index], node))
+                .collect(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn iter_mut_groups(&mut self) -> IterMutGroups<'_, Data> {
+        IterMutGroups {
+            level: self.level(),
+            cells: self
+                .cells
+                .nodes_mut()
+                .into_iter()
+                .map(|(index, node)| (vec![index], node))
+                .collect(),
+        }
+    }
+}
+
+impl<Data: Clone> Diagram<Data> {
+    pub fn cell(&self, cell: &ViewIndex) -> Result<super::Cell<Data>, Error> {
+        if cell.level() == self.level() {
+            let path = self.valid_level(cell).unwrap();
+
+            self.get(&path)
+                .map(|cell| super::Cell::Leveled(cell.clone()))
+                .ok_or(Error::NoSuchCell(cell.clone()))
+
+        } else if cell.level() < self.level() {
+            self.prev.cell(cell)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    pub fn cell_by_id(&self, id: CellId) -> Result<super::Cell<Data>, Error> {
+        self.cell(&self.index_of(id)?)
+    }
+}
+
+// IMPL: Transforming
+//
+impl<Data> Diagram<Data> {
+    pub fn into_next(&mut self, wraps: Vec<MetaCell<Data>>) -> Result<(), Error> {
+        take_mut::take(self, |this| {
+            let mut next = Self {
+                prev: Tail::Diagram(Box::new(this)),
+                cells: fill![],
+            };
+
+            for wrap in wraps {
+                let MetaCell { data, face: Face { ends, fill } } = wrap;
+
+                next.wrap_extrusion(data, &ends, fill);// NOTE: If this panics, program aborts.
+            }
+
+            next
+        });
+
+        Ok(())
+    }
+}
+
+// IMPL: Editing
+//
+impl<Data: Clone> Diagram<Data> {
+    interaction!{
+    extrude(&mut self, cells: &Selection, group: Data, wrap: Data)
+        in prev {
+            self
+                .prev
+                .extrude(cells, group, wrap.clone())
+                .map(|inter| {
+                    let (fill, ends) = extract![inter => group, contents in Interaction::Here { action: Action::Extrude { group, contents } }];
+
+                    cells
+                        .as_cells()
+                        .iter()
+                        .zip(ends.iter())
+                        .for_each(|(cell, end)| {
+                            self.replace_line(&cell.path(), &end.path()).unwrap()
+                        });
+
+                    let wraps = vec![self.wrap_extrusion(wrap, &ends, fill.clone())];
+
+                    Interaction::InPrevious {
+                        wraps,
+                        action: Action::Extrude { group: fill, contents: ends },
+                    }
+                })
+        }
+
+        in self {
+            if !cells.common_path().is_empty() {
+                return EditResult::Err(Error::CannotExtrudeNestedCells(cells.clone()));
+            }
+
+            match self.group(&cells.as_paths(), group) {
+                Ok((group, contents)) => {
+                        EditResult::Ok(Interaction::Here {
+                            action: Action::Extrude { group, contents },
+                        })
+                    },
+
+                Err(e) => EditResult::Err(e),
+            }
+        }
+    }
+
+    interaction!{
+    split(&mut self, cells: &Selection, group: Data, wrap_top: Data, wrap_bot: Data)
+        in prev {
+            self
+                .prev
+                .split(cells, group, wrap_top.clone(), wrap_bot.clone())
+                .map(|inter| {
+                    let (fill, ends) = extract![inter => group, contents in Interaction::Here { action: Action::Split { group, contents } }];
+
+                    cells
+                        .as_cells()
+                        .iter()
+                        .zip(ends.iter())
+                        .for_each(|(cell, end)| {
+                            self.replace_line(&cell.path(), &end.path()).unwrap()
+                        });
+
+                    let wraps = self.wrap_split(wrap_top, wrap_bot, &ends, fill.clone()).to_vec();
+
+                    Interaction::InPrevious {
+                        wraps,
+                        action: Action::Extrude { group: fill, contents: ends },
+                    }
+                })
+        }
+
+        in self {
+            match self.group(&cells.as_paths(), group) {
+                Ok((group, contents)) =>
+                    EditResult::Ok(Interaction::Here {
+                        action: Action::Split { group, contents },
+                    }),
+
+                Err(e) =>
+                    EditResult::Err(e),
+            }
+        }
+    }
+
+    interaction!{
+    sprout(&mut self, index: &ViewIndex, end: Data, wrap: Data)
+        in prev {
+            self
+                .prev
+                .sprout(index, end, wrap.clone())
+                .map(|inter| {
+                    let (fill, end) = extract![inter => group, end in Interaction::Here { action: Action::Sprout { group, end } }];
+
+                    self.replace_line(&index.path(), &fill.path()).unwrap();
+
+                    let wraps = vec![self.wrap_sprout(wrap, &fill, end.clone())];
+
+                    Interaction::InPrevious {
+                        wraps,
+                        action: Action::Sprout { group: fill, end },
+                    }
+                })
+        }
+
+        in self {
+            match self.is_end(index) {
+                Ok(is_end) =>
+                    if !is_end {
+                        return EditResult::Err(Error::CannotSproutGroup(index.clone()));
+                    },
+
+                Err(e) => return EditResult::Err(e),
+            }
+
+            if let Some(group) = self.cells.handle(&index.path()) {
+                let face = self.cells.get(group).unwrap().face.clone();
+
+                let end = MetaCell {
+                    data: end,
+
+                    face,
+                };
+
+                let (innermost_index, _) = self.cells.push(Some(group), end);
+
+                let mut path = index.path();
+
+                path.push(innermost_index);
+
+                let level = self.level() - 1;
+
+                EditResult::Ok(Interaction::Here {
+                    action: Action::Sprout { group: index.clone(), end: ViewIndex::Leveled { level, path } },
+                })
+
+            } else {
+                EditResult::Err(Error::NoSuchCell(index.clone()))
+            }
+        }
+    }
+}
+
+impl<Data: Clone> Diagram<Data> {
+    fn group(&mut self, cells: &[Vec<TimelessIndex>], data: Data) -> Result<(ViewIndex, Vec<ViewIndex>), Error> {
+        let (tail, cells) = self.check_form_tree(cells)?;
+
+        let face = Face::collect(
+            self.cell_space(tail)
+                .iter_timeless_indices()
+                .filter(|(index, _)| cells.contains(index))
+                .map(|(_, &cell)| &self.cells.get(cell).unwrap().face)
+        );
+
+        let owner = self.cells.handle(tail);
+
+        let (index, group) =
+        self.cells
+            .group(owner, &cells, MetaCell { data, face })
+            .map_err(Error::IndexError)?;
+
+        let mut group_path = tail.to_vec();
+        group_path.push(index);
+
+
+        let contents_indices =
+        self.cells
+            .children(group)
+            .unwrap()
+            .timeless_indices()
+            .map(|i| {
+                let mut member_path = group_path.clone();
+                member_path.push(i);
+
+                self.to_index(member_path)
+            })
+            .collect();
+
+        Ok((self.to_index(group_path), contents_indices))
+    }
+}
+
+impl<Data> Diagram<Data> {
+    fn wrap_sprout(&mut self, data: Data, old_end_line: &ViewIndex, sprouted_line: ViewIndex) -> ViewIndex {
+        let mut cells_below =
+        self.cells
+            .nodes()
+            .into_iter()
+            .map(|(_, node)| (node.handle(), node.val()));
+
+        let mut cells_with_fill_before = cells_below
+            .take_while_ref(|(_, cell)|
+                !cell.face().ends.contains(old_end_line)
+            )
+            .encapsulate();
+
+        // NOTE: `cells_below` are now the downstream cells.
+        // NOTE: `cells_with_fill_before` are the upstream cells and the parallel cells.
+
+        let cells_with_end_after = cells_with_fill_before
+            .take_while_ref(|(_, cell)| !self.has_fill_before(cell, old_end_line))
+            .encapsulate();
+
+        // NOTE: `cells_with_fill_before` are now cells between the downstream cells and the
+        // preceding cells.
+
+        // NOTE: `cells_with_end_after` are the upstream cells and the parallel cells running
+        // into the ends to the right of `old_end_line`.
+
+        let mut cells_below = cells_below.proj_l();
+        let mut cells_with_end_after = cells_with_end_after.proj_l();
+
+        let cells_with_fill_before = cells_with_fill_before.proj_l();
+
+        let wrap = MetaCell {
+            data,
+            face: Face {
+                ends: vec![sprouted_line],
+                fill: old_end_line.clone(),
+            },
+        };
+
+        let (index, _) =
+        if let Some(preceding) = cells_with_fill_before.last() {
+            self.cells.insert_after(preceding, wrap).unwrap()
+
+        } else if let Some(succeeding) = cells_with_end_after.next().or(cells_below.next()) {
+            self.cells.insert_before(succeeding, wrap).unwrap()
+
+        } else {
+            self.cells.push(None, wrap)
+        };
+
+        self.to_index(vec![index])
+    }
+
+    fn wrap_extrusion(&mut self, data: Data, old_fill_lines: &[ViewIndex], created_line: ViewIndex) -> ViewIndex {
+        let mut upstream_outputs = self.sort_lines(old_fill_lines);
+
+        let lowest_fill_line = upstream_outputs.pop().unwrap();
+
+        let mut cells_next_to =
+        self.cells
+            .nodes()
+            .into_iter()
+            .map(|(_, node)| (node.handle(), node.val()));
+
+        let cells_above = cells_next_to
+            .take_while_ref(|(_, cell)| cell
+                .face()
+                .ends
+                .iter()
+                .all(|end| self.prev.is_before(end, &lowest_fill_line))
+            )
+            .proj_l();
+
+        let cells_after = cells_next_to
+            .take_while_ref(|(_, cell)| self.prev.is_before(&lowest_fill_line, &cell.face().fill))
+            .proj_l();
+
+        let mut cells_next_to = cells_next_to.proj_l();
+
+        let wrap = MetaCell {
+            data,
+            face: Face {
+                ends: old_fill_lines.to_vec(),
+                fill: created_line,
+            },
+        };
+
+
+        let (index, _) =
+        if let Some(cell_after) = cells_next_to.next().or(cells_after.last()) {
+            self.cells.insert_before(cell_after, wrap).unwrap()
+
+        } else if let Some(cell_before) = cells_above.last() {
+            self.cells.insert_after(cell_before, wrap).unwrap()
+
+        } else {
+            self.cells.push(None, wrap)
+        };
+
+        self.to_index(vec![index])
+    }
+
+    fn wrap_split(&mut self, wrap_top: Data, wrap_bot: Data, upstream_lines: &[ViewIndex], created_line: ViewIndex) -> [ViewIndex; 2] {
+        let (old_wrap, prefix, postfix) = self.cell_with_inputs(upstream_lines).unwrap();
+
+        let path = self.cells.path(old_wrap).unwrap();
+
+        let wrap_top = MetaCell {
+            data: wrap_top,
+            face: Face {
+                ends: upstream_lines.to_vec(),
+                fill: created_line.clone(),
+            },
+        };
+
+        let mut ends = prefix;
+        ends.push(created_line);
+        ends.extend(postfix);
+
+        let wrap_bot = MetaCell {
+            data: wrap_bot,
+            face: Face {
+                ends,
+                fill: self.cells.get(old_wrap).unwrap().face.fill.clone(),
+            },
+        };
+
+        let (top, _) = self.cells.push(Some(old_wrap), wrap_top);
+        let (bot, _) = self.cells.push(Some(old_wrap), wrap_bot);
+
+        let top = {
+            let mut path = path.clone();
+
+            path.push(top);
+
+            path
+        };
+        let bot = {
+            let mut path = path;
+
+            path.push(bot);
+
+            path
+        };
+
+        [self.to_index(top), self.to_index(bot)]
+    }
+}
+
+// IMPL: Cell manipulation
+//
+impl<Data> Diagram<Data> {
+    pub fn rename(&mut self, cell: &ViewIndex, new_data: Data) -> Result<(), Error> {
+        if cell.level() == self.level() {
+            let path = self.valid_level(cell).unwrap();
+
+            let cell = self.get_mut(&path).ok_or(Error::NoSuchCell(cell.clone()))?;
+            cell.data = new_data;
+
+            Ok(())
+
+        } else if cell.level() < self.level() {
+            self.prev.rename(cell, new_data)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+}
+
+// IMPL: Deleting
+//
+impl<Data: Clone> Diagram<Data> {
+    /// Deletes an end cell, or dissolves a group into its parent cell space, at any level. Cells
+    /// of higher levels which only wrap the deleted cell (as made by extruding or sprouting) are
+    /// deleted along with it, any other use of the cell is an error. So is deleting a cell which
+    /// connects other cells of its level, or one which would leave a level without cells.
+    ///
+    /// On error, the opetope is left untouched.
+    ///
+    pub fn delete(&mut self, cell: &ViewIndex) -> EditResult<Interaction, Data> {
+        let mut edited = self.clone();
+        let mut contents = vec![];
+        let mut wraps = vec![];
+
+        let deleted =
+        edited
+            .delete_helper(cell, &mut contents, &mut wraps)
+            .and_then(|_| edited.check_levels(self, cell));
+
+        if let Err(e) = deleted {
+            return EditResult::Err(e);
+        }
+
+        *self = edited;
+
+        // NOTE: Only groups have contents.
+        //
+        let action =
+        if contents.is_empty() {
+            Action::Delete { cell: cell.clone() }
+
+        } else {
+            Action::Ungroup { group: cell.clone(), contents }
+        };
+
+        if cell.level() == self.level() {
+            EditResult::Ok(Interaction::Here { action })
+
+        } else {
+            EditResult::Ok(Interaction::InPrevious { action, wraps })
+        }
+    }
+
+    /// Dissolves the group into its parent cell space, at any level. The cells of the next level
+    /// wrapping the group are deleted, faces referencing its contents follow them to their new
+    /// place.
+    ///
+    pub fn ungroup(&mut self, group: &ViewIndex) -> EditResult<Interaction, Data> {
+        match self.is_group_deep(group) {
+            Ok(true) => self.delete(group),
+            Ok(false) => EditResult::Err(Error::NotAGroup(group.clone())),
+
+            Err(e) => EditResult::Err(e),
+        }
+    }
+
+    /// Records the new indices of the contents of a dissolved group, and the wraps removed from the
+    /// level above the deleted cell.
+    ///
+    fn delete_helper(&mut self, cell: &ViewIndex, contents: &mut Vec<ViewIndex>, wraps: &mut Vec<ViewIndex>) -> Result<Removal, Error> {
+        if cell.level() == self.level() {
+            let removal = self.remove(cell)?;
+
+            *contents = removal.moved.iter().map(|(_, new)| new.clone()).collect();
+
+            Ok(removal)
+
+        } else if cell.level() < self.level() {
+            let removal =
+            match &mut self.prev {
+                Tail::Tower(t) => t.remove(cell)?,
+                Tail::Diagram(d) => d.delete_helper(cell, contents, wraps)?,
+            };
+
+            let removal = self.remove_wraps(removal)?;
+
+            if cell.level() + 1 == self.level() {
+                *wraps = removal.cells.clone();
+            }
+
+            Ok(removal)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    /// Checks that the cells of every level from the deleted cell up still form a tree, unless the
+    /// level had no cells to begin with.
+    ///
+    fn check_levels(&self, before: &Self, cell: &ViewIndex) -> Result<(), Error> {
+        let roots = self.cells.nodes();
+
+        let (_, outputs) = Self::boundary(&roots);
+
+        if roots.is_empty() {
+            if !before.cells.nodes().is_empty() {
+                return Err(Error::CannotDeleteOnlyCell(cell.clone()));
+            }
+
+        } else if outputs.len() != 1 {
+            return Err(Error::NotAnEnd(cell.clone()));
+        }
+
+        match (&self.prev, &before.prev) {
+            (Tail::Diagram(prev), Tail::Diagram(before)) if cell.level() < self.level() =>
+                prev.check_levels(before, cell),
+
+            _ => Ok(()),
+        }
+    }
+
+    fn remove(&mut self, cell: &ViewIndex) -> Result<Removal, Error> {
+        let path = self.valid_level(cell)?;
+
+        let (_, parent) =
+        path.split_last()
+            .ok_or_else(|| Error::NoSuchCell(cell.clone()))?;
+
+        let removed =
+        self.cells
+            .handle(&path)
+            .ok_or_else(|| Error::NoSuchCell(cell.clone()))?;
+
+        if self.cells.is_group(removed) {
+            // NOTE: The contents of a dissolved group take its place.
+            //
+            let spliced =
+            self.cells
+                .ungroup(removed)
+                .ok_or_else(|| Error::NoSuchCell(cell.clone()))?;
+
+            let moved =
+            spliced
+                .into_iter()
+                .map(|(inner, new)| {
+                    let mut old_path = path.clone();
+                    old_path.push(inner);
+
+                    let mut new_path = parent.to_vec();
+                    new_path.push(new);
+
+                    (self.to_index(old_path), self.to_index(new_path))
+                })
+                .collect_vec();
+
+            let wrap = Face {
+                ends: moved.iter().map(|(old, _)| old.clone()).collect(),
+                fill: cell.clone(),
+            };
+
+            Ok(Removal { cells: vec![cell.clone()], wraps: vec![wrap], moved })
+
+        } else if parent.is_empty() {
+            self.cells.remove(removed);
+
+            Ok(Removal { cells: vec![cell.clone()], wraps: vec![], moved: vec![] })
+
+        } else {
+            // NOTE: Only a sprout can be removed from its group, which becomes an end again.
+            //
+            let group = self.cells.parent(removed).unwrap();
+
+            let sprouted =
+            self.cells
+                .children(group)
+                .map(|content| content.iter().count() == 1)
+                .unwrap_or(false)
+                && self.cells.get(group).unwrap().face().matches(self.cells.get(removed).unwrap().face());
+
+            if !sprouted {
+                return Err(Error::CannotDeleteFromGroup(cell.clone()));
+            }
+
+            self.cells.remove(removed);
+
+            let wrap = Face {
+                ends: vec![cell.clone()],
+                fill: self.to_index(parent.to_vec()),
+            };
+
+            Ok(Removal { cells: vec![cell.clone()], wraps: vec![wrap], moved: vec![] })
+        }
+    }
+
+    /// Removes the cells wrapping the lines removed from the previous level, and follows the lines
+    /// which were moved.
+    ///
+    fn remove_wraps(&mut self, removal: Removal) -> Result<Removal, Error> {
+        let Removal { cells: lines, wraps, moved } = removal;
+
+        let mut removed = vec![];
+        let mut blocked = None;
+
+        for (path, handle) in self.cells.paths() {
+            let index = self.to_index(path);
+            let face = self.cells.get(handle).unwrap().face();
+
+            let used =
+            face.ends
+                .iter()
+                .chain(Some(&face.fill))
+                .find(|line| lines.contains(line));
+
+            if let Some(line) = used {
+                let is_wrap =
+                index.path().len() == 1
+                    && !self.cells.is_group(handle)
+                    && wraps.iter().any(|wrap| wrap.matches(face));
+
+                if is_wrap {
+                    removed.push(index);
+
+                } else {
+                    blocked
+                        .get_or_insert_with(|| (line.clone(), vec![]))
+                        .1
+                        .push(index);
+                }
+            }
+        }
+
+        if let Some((line, users)) = blocked {
+            return Err(Error::CellIsReferenced(line, users));
+        }
+
+        for index in &removed {
+            let wrap =
+            self.cells
+                .handle(&index.path())
+                .ok_or_else(|| Error::NoSuchCell(index.clone()))?;
+
+            self.cells.remove(wrap);
+        }
+
+        for (old, new) in &moved {
+            self.replace_line(&old.path(), &new.path())?;
+        }
+
+        Ok(Removal { cells: removed, wraps: vec![], moved: vec![] })
+    }
+}
+
+// IMPL: Utils
+//
+impl<Data> Diagram<Data> {
+    // NOTE: Cells at any depth are visited at once, since the tree keeps them side by side.
+    //
+    fn replace_line(&mut self, line: &[TimelessIndex], new: &[TimelessIndex]) -> Result<(), Error> {
+        for cell in self.cells.iter_mut() {
+            cell.face.fill.subst_prefix(line, new);
+
+            cell.face
+                .ends
+                .iter_mut()
+                .for_each(|end| end.subst_prefix(line, new));
+        }
+
+        Ok(())
+    }
+
+
+    fn sort_lines(&self, lines: &[ViewIndex]) -> Vec<ViewIndex> {
+        let mut lines = lines.to_vec();
+
+        lines.sort_by(|a, b| {
+            if self.prev.is_before(a, b) {
+                std::cmp::Ordering::Less
+
+            } else {
+                std::cmp::Ordering::Greater
+            }
+        });
+
+        lines
+    }
+
+    fn has_fill_before(&self, cell: &MetaCell<Data>, end: &ViewIndex) -> bool {
+        self.prev.is_before(&cell.face.fill, end)
+    }
+
+    /// The end cell whose ends contain `inputs` in a row, along with its ends before and after
+    /// them.
+    ///
+    fn cell_with_inputs(&self, inputs: &[ViewIndex]) -> Result<(Handle, Vec<ViewIndex>, Vec<ViewIndex>), Error> {
+        for (_, handle) in self.cells.paths() {
+            if self.cells.is_group(handle) {
+                continue;
+            }
+
+            let ends = &self.cells.get(handle).unwrap().face.ends;
+
+            for (i, sub) in ends.windows(inputs.len()).enumerate() {
+                if sub == inputs {
+                    let prefix = ends[.. i].to_vec();
+                    let postfix = ends[i + inputs.len() ..].to_vec();
+
+                    return Ok((handle, prefix, postfix));
+                }
+            }
+        }
+
+        Err(Error::NoCellWithInputs(inputs.to_vec()))
+    }
+
+
+    fn cell_space(&self, path: &[TimelessIndex]) -> &TracingVec<Handle> {
+        if let Some(owner) = self.cells.handle(path) {
+            self.cells
+                .children(owner)
+                .unwrap()
+
+        } else {
+            self.cells.roots()
+        }
+    }
+
+
+    #[allow(dead_code)]
+    fn input_count(&self) -> usize {
+        let outputs =
+        self.cells
+            .nodes()
+            .into_iter()
+            .map(|(_, node)| &node.val().face.fill)
+            .collect_vec();
+
+        self.cells
+            .nodes()
+            .into_iter()
+            .flat_map(|(_, node)| &node.val().face.ends)
+            .filter(|line| !outputs.contains(line))
+            .count()
+    }
+
+
+    fn to_index(&self, path: Vec<TimelessIndex>) -> ViewIndex {
+        ViewIndex::Leveled {
+            level: self.level() - 1,
+
+            path,
+        }
+    }
+
+    // NOTE: Expects whole path to the cell.
+    //
+    fn check_form_tree<'c>(&self, cells: &'c [Vec<TimelessIndex>]) -> Result<(&'c [TimelessIndex], Vec<TimelessIndex>), Error> {
+        let ret = self.check_cells_connected(cells)?;
+
+        let inputs: Vec<_> =
+        cells
+            .iter()
+            .flat_map(|path| &self.get(path).unwrap().face().ends)
+            .collect();
+
+        let dangling =
+        cells
+            .iter()
+            .map(|path| &self.get(path).unwrap().face().fill)
+            .filter(|line| !inputs.contains(line))
+            .count();
+
+        if dangling == 1 {
+            Ok(ret)
+
+        } else {
+            Err(Error::CellsDoNotFormTree(
+                cells
+                    .iter()
+                    .cloned()
+                    .map(|path| self.to_index(path))
+                    .collect()
+            ))
+        }
+    }
+
+    // NOTE: Expects whole path to the cell.
+    //
+    fn check_cells_connected<'c>(&self, cells: &'c [Vec<TimelessIndex>]) -> Result<(&'c [TimelessIndex], Vec<TimelessIndex>), Error> {
+        let heads: Vec<_> =
+        cells
+            .iter()
+            .map(|path| path.last().unwrap())
+            .copied()
+            .collect();
+
+        let mut tails: Vec<_> =
+        cells
+            .iter()
+            .map(|path| &path[.. path.len() - 1])
+            .collect();
+
+        let tail =
+        tails
+            .pop()
+            .unwrap();
+
+        if tails
+            .into_iter()
+            .all(|tl| tail == tl)
+        {
+            Ok((tail, heads))
+
+        } else {
+            Err(Error::CannotGroupDisconnected(
+                cells
+                    .iter()
+                    .cloned()
+                    .map(|path| self.to_index(path))
+                    .collect()
+            ))
+        }
+    }
+}
+
+/// Common opetope interface
+///
+impl<Data> Diagram<Data> {
+    pub fn is_end(&self, cell: &ViewIndex) -> Result<bool, Error> {
+        let path = self.valid_level(cell)?;
+
+        let handle =
+        self.cells
+            .handle(&path)
+            .ok_or(Error::NoSuchCell(cell.clone()))?;
+
+        Ok(!self.cells.is_group(handle))
+    }
+
+    fn is_group_deep(&self, cell: &ViewIndex) -> Result<bool, Error> {
+        if cell.level() == self.level() {
+            Ok(!self.is_end(cell)?)
+
+        } else if cell.level() < self.level() {
+            match &self.prev {
+                Tail::Tower(t) =>
+                    if t.contains(cell) {
+                        Ok(false)
+
+                    } else {
+                        Err(Error::NoSuchCell(cell.clone()))
+                    },
+
+                Tail::Diagram(d) =>
+                    d.is_group_deep(cell),
+            }
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    pub fn id_of(&self, cell: &ViewIndex) -> Result<CellId, Error> {
+        if cell.level() == self.level() {
+            self.cells
+                .handle(&cell.path())
+                .and_then(|handle| self.cells.id(handle))
+                .map(|number| CellId { level: self.level(), number })
+                .ok_or_else(|| Error::NoSuchCell(cell.clone()))
+
+        } else if cell.level() < self.level() {
+            self.prev.id_of(cell)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    pub fn index_of(&self, id: CellId) -> Result<ViewIndex, Error> {
+        if id.level == self.level() {
+            self.cells
+                .find(id.number)
+                .and_then(|handle| self.cells.path(handle))
+                .map(|path| self.to_index(path))
+                .ok_or(Error::NoSuchId(id))
+
+        } else if id.level < self.level() {
+            self.prev.index_of(id)
+
+        } else {
+            Err(Error::TooMuchDepth(id.level))
+        }
+    }
+
+    /// Identifiers of all cells of this level along with their current indices, each group
+    /// preceding its contents.
+    ///
+    pub fn ids(&self) -> Vec<(CellId, ViewIndex)> {
+        let level = self.level();
+
+        self.cells
+            .paths()
+            .into_iter()
+            .map(|(path, handle)| {
+                let number = self.cells.id(handle).unwrap();
+
+                (CellId { level, number }, self.to_index(path))
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, cell: &ViewIndex) -> bool {
+        if cell.level() == self.level() {
+            self.get(&cell.path()).is_some()
+
+        } else if cell.level() < self.level() {
+            self.prev.contains(cell)
+
+        } else {
+            false
+        }
+    }
+
+    /// The cell one step from `cell` in `direction`, if there is one.
+    ///
+    pub fn neighbour(&self, cell: &ViewIndex, direction: Direction) -> Option<ViewIndex> {
+        if cell.level() + 1 == self.level() && direction == Direction::Up {
+            let faces =
+            self.indices()
+                .into_iter()
+                .map(|index| (self.get(&index.path()).unwrap().face().clone(), index))
+                .collect_vec();
+
+            return faces
+                .iter()
+                .find(|(face, _)| &face.fill == cell)
+                .or_else(|| faces.iter().find(|(face, _)| face.ends.contains(cell)))
+                .map(|(_, index)| index.clone());
+
+        } else if cell.level() < self.level() {
+            return self.prev.neighbour(cell, direction);
+        }
+
+        let path = self.valid_level(cell).ok()?;
+        let tail = cell.tail();
+
+        let this = self.cells.handle(&path)?;
+
+        match direction {
+            Direction::Up =>
+                None,
+
+            Direction::Down =>
+                Some(self.cells.get(this)?.face.fill.clone()),
+
+            Direction::Left | Direction::Right => {
+                let siblings =
+                self.cell_space(&tail)
+                    .timeless_indices()
+                    .collect_vec();
+
+                let at = siblings.iter().position(|index| Some(index) == path.last())?;
+
+                let to =
+                if direction == Direction::Left {
+                    at.checked_sub(1)?
+
+                } else {
+                    at + 1
+                };
+
+                let mut path = tail;
+                path.push(*siblings.get(to)?);
+
+                Some(self.to_index(path))
+            },
+
+            Direction::In => {
+                let mut path = path;
+                path.push(self.cells.children(this)?.timeless_indices().next()?);
+
+                Some(self.to_index(path))
+            },
+
+            Direction::Out =>
+                if tail.is_empty() {
+                    None
+
+                } else {
+                    Some(self.to_index(tail))
+                },
+        }
+    }
+
+    pub fn is_before(&self, before: &ViewIndex, after: &ViewIndex) -> bool {
+        let mut before = self.valid_level(before).unwrap();
+        let mut after = self.valid_level(after).unwrap();
+
+        let common_path = Self::remove_common_prefix(&mut before, &mut after);
+
+        let cell_space = self.cell_space(&common_path);
+
+        if let ([before, ..], [after, ..]) = (&before[..], &after[..]) {
+            cell_space.is_before(*before, *after).unwrap()
+
+        } else {
+            true
+        }
+    }
+
+    pub fn is_at_bottom(&self, cells: &Selection) -> Result<bool, Error> {
+        if cells.level() > self.level() {
+            Err(Error::TooMuchDepth(cells.level()))
+
+        } else if cells.level() == self.level() {
+            let bottom =
+            self.cells
+                .roots()
+                .timeless_indices()
+                .map(|index| self.to_index(vec![index]))
+                .collect_vec();
+
+            Ok(
+                cells
+                    .as_cells()
+                    .iter()
+                    .all(|cell| bottom.contains(cell))
+            )
+
+        } else {
+            self.prev.is_at_bottom(cells)
+        }
+    }
+
+    fn remove_common_prefix<X>(path_a: &mut Vec<X>, path_b: &mut Vec<X>) -> Vec<X> where X: PartialEq {
+        path_a.reverse();
+        path_b.reverse();
+
+        let mut common = vec![];
+
+        while !path_a.is_empty() && !path_b.is_empty() && path_a.last() == path_b.last() {
+            let x = path_a.pop().unwrap();
+            path_b.pop();
+
+            common.push(x);
+        }
+
+        path_a.reverse();
+        path_b.reverse();
+
+        common
+    }
+}
+
+
+/// Validation
+///
+impl<Data> Diagram<Data> {
+    fn valid_level(&self, index: &ViewIndex) -> Result<Vec<TimelessIndex>, Error> {
+        if self.level() == index.level() {
+            Ok(index.path())
+
+        } else {
+            Err(Error::TooMuchDepth(index.level()))
+        }
+    }
+}
+
+impl<Data: Clone> Diagram<Data> {
+    pub fn deep_copy(&self, level: usize) -> Result<Tail<Data>, Error> {
+        if self.level() == level {
+            Ok(Tail::Diagram(Box::new(self.clone())))
+
+        } else {
+            self.prev.deep_copy(level)
+        }
+    }
+}
+
+// IMPL: Selections
+//
+impl<Data> Diagram<data::Selectable<Data>> {
+    pub fn select(&mut self, cell: &ViewIndex) -> Result<Option<Selection>, Error> {
+        if self.level() == cell.level() {
+            self.prev.unselect_all(0);
+
+            if let Some(selection) = self.selected_cells() {
+                if selection.common_path() != cell.tail() {
+                    self.unselect_all(self.level());
+                }
+            }
+
+            self.select_unchecked(cell)?;
+
+            Ok(self.selected_cells())
+
+        } else if self.level() > cell.level() {
+            self.unselect_all(self.level());
+
+            self.prev
+                .select(cell)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    /// Extends the selection by the cells connecting it to `cell`, so that the selected cells form
+    /// the smallest tree containing both. Falls back to [`Self::select`] if `cell` isn't in the
+    /// cell space of the selection.
+    ///
+    pub fn select_range(&mut self, cell: &ViewIndex) -> Result<Option<Selection>, Error> {
+        if self.level() > cell.level() {
+            self.unselect_all(self.level());
+
+            return self.prev.select_range(cell);
+        }
+
+        let tail = cell.tail();
+
+        let selected =
+        match self.selected_cells_no_prev() {
+            Some(Selection::Leveled { path, cells, .. }) if self.level() == cell.level() && path == tail =>
+                cells,
+
+            _ => return self.select(cell),
+        };
+
+        let target = *cell.path().last().unwrap();
+
+        self.cell_space(&tail)
+            .get(target)
+            .map_err(|_| Error::NoSuchCell(cell.clone()))?;
+
+        match self.connect(self.cell_space(&tail), target, &selected) {
+            Some(way) => {
+                self.prev.unselect_all(0);
+
+                for index in way {
+                    let mut path = tail.clone();
+                    path.push(index);
+
+                    self.get_mut(&path)
+                        .unwrap()
+                        .data_mut()
+                        .set_selected(true);
+                }
+
+                Ok(self.selected_cells())
+            },
+
+            None => self.select(cell),
+        }
+    }
+
+    /// Selects every cell in the cell space of the selection, or in the outermost one if nothing
+    /// is selected. The selected cells stay selected.
+    ///
+    pub fn select_all(&mut self) -> Option<Selection> {
+        match self.selected_cells() {
+            Some(selection) if selection.level() < self.level() => {
+                self.prev.select_all();
+            },
+
+            selection => {
+                let tail =
+                selection
+                    .map(|selection| selection.common_path())
+                    .unwrap_or_default();
+
+                let indices: Vec<_> =
+                self.cell_space(&tail)
+                    .timeless_indices()
+                    .collect();
+
+                for index in indices {
+                    let mut path = tail.clone();
+                    path.push(index);
+
+                    self.get_mut(&path)
+                        .unwrap()
+                        .data_mut()
+                        .set_selected(true);
+                }
+            },
+        }
+
+        self.selected_cells()
+    }
+
+    /// The cells on the shortest way from `from` to any of `to`, where cells are adjacent if the
+    /// output of one is an input of the other.
+    ///
+    fn connect(&self, cell_space: &TracingVec<Handle>, from: TimelessIndex, to: &[TimelessIndex]) -> Option<Vec<TimelessIndex>> {
+        let cells: Vec<_> =
+        cell_space
+            .iter_timeless_indices()
+            .map(|(index, &handle)| (index, self.cells.get(handle).unwrap()))
+            .collect();
+
+        let adjacent = |a: &Face, b: &Face| a.ends.contains(&b.fill) || b.ends.contains(&a.fill);
+
+        let mut previous = HashMap::new();
+        let mut visited = vec![from];
+        let mut queue = VecDeque::from(vec![from]);
+
+        while let Some(index) = queue.pop_front() {
+            if to.contains(&index) {
+                let mut way = vec![index];
+
+                while let Some(&prev) = previous.get(way.last().unwrap()) {
+                    way.push(prev);
+                }
+
+                return Some(way);
+            }
+
+            let (_, cell) =
+            cells.iter()
+                .find(|(i, _)| *i == index)
+                .unwrap();
+
+            for (next, other) in &cells {
+                if !visited.contains(next) && adjacent(cell.face(), other.face()) {
+                    visited.push(*next);
+                    previous.insert(*next, index);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Moves the keyboard focus to `cell`.
+    ///
+    pub fn focus(&mut self, cell: &ViewIndex) -> Result<(), Error> {
+        if self.level() == cell.level() {
+            let path = self.valid_level(cell)?;
+
+            self.get(&path)
+                .ok_or(Error::NoSuchCell(cell.clone()))?;
+
+            self.unfocus_all();
+
+            self.get_mut(&path)
+                .unwrap()
+                .data_mut()
+                .focus();
+
+            Ok(())
+
+        } else if self.level() > cell.level() {
+            self.prev.focus(cell)?;
+
+            self.cells
+                .iter_mut()
+                .for_each(|cell| cell.data.unfocus());
+
+            Ok(())
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    pub fn unfocus_all(&mut self) {
+        self.prev.unfocus_all();
+
+        self.cells
+            .iter_mut()
+            .for_each(|cell| cell.data.unfocus())
+    }
+
+    pub fn focused_cell(&self) -> Option<ViewIndex> {
+        self.prev
+            .focused_cell()
+            .or_else(||
+                self.indices()
+                    .into_iter()
+                    .find(|index| self.get(&index.path()).unwrap().data().focused())
+            )
+    }
+
+    /// Marks `cell` as a part of the face of a selected cell.
+    ///
+    pub fn highlight(&mut self, cell: &ViewIndex, highlight: data::Highlight) -> Result<(), Error> {
+        if self.level() == cell.level() {
+            let path = self.valid_level(cell)?;
+
+            self.get_mut(&path)
+                .ok_or(Error::NoSuchCell(cell.clone()))?
+                .data_mut()
+                .highlight(highlight);
+
+            Ok(())
+
+        } else if self.level() > cell.level() {
+            self.prev.highlight(cell, highlight)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    /// Marks `cell` as the one under the cursor.
+    ///
+    pub fn hover(&mut self, cell: &ViewIndex) -> Result<(), Error> {
+        if self.level() == cell.level() {
+            let path = self.valid_level(cell)?;
+
+            self.get_mut(&path)
+                .ok_or(Error::NoSuchCell(cell.clone()))?
+                .data_mut()
+                .hover();
+
+            Ok(())
+
+        } else if self.level() > cell.level() {
+            self.prev.hover(cell)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    pub fn unhighlight_all(&mut self) {
+        self.prev.unhighlight_all();
+
+        self.cells
+            .iter_mut()
+            .for_each(|cell| cell.data.unhighlight())
+    }
+
+    pub(in super) fn select_unchecked(&mut self, cell: &ViewIndex) -> Result<(), Error> {
+        self.get_mut(&cell.path())
+            .ok_or(Error::NoSuchCell(cell.clone()))?
+            .data_mut()
+            .select();
+
+        Ok(())
+    }
+
+    pub fn unselect_all(&mut self, max_depth: usize) {
+        if max_depth < self.level() {
+            self.prev.unselect_all(max_depth);
+        }
+
+        self.cells
+            .iter_mut()
+            .for_each(|cell| cell.data.unselect())
+    }
+
+    pub fn selected_cells(&self) -> Option<Selection> {
+        if let Some(sel) = self.prev.selected_cells() {
+            Some(sel)
+
+        } else {
+            self.selected_cells_no_prev()
+        }
+    }
+
+    fn selected_cells_no_prev(&self) -> Option<Selection> {
+        let mut all_selected = vec![];
+
+        Self::selected_helper(self.cells.nodes(), &[], &mut all_selected);
+
+        let first = all_selected.first()?;
+
+        let path = first[.. first.len() - 1].to_vec();
+        let level = self.level() - 1;
+
+        assert![all_selected.iter().all(|p| path == p[.. p.len() - 1])];
+
+        let cells =
+        all_selected
+            .into_iter()
+            .map(|mut path| path.pop().unwrap())
+            .collect();
+
+        Some(Selection::Leveled {
+            level,
+            path,
+            cells,
+        })
+    }
+
+    // NOTE: The contents of a selected group aren't visited.
+    //
+    fn selected_helper(nodes: Vec<(TimelessIndex, NodeRef<MetaCell<data::Selectable<Data>>>)>, prefix: &[TimelessIndex], selected: &mut Vec<Vec<TimelessIndex>>) {
+        for (index, node) in nodes {
+            let mut path = prefix.to_vec();
+            path.push(index);
+
+            if node.val().data().selected() {
+                selected.push(path);
+
+            } else if let Some(children) = node.children() {
+                Self::selected_helper(children, &path, selected);
+            }
+        }
+    }
+}
+
+impl<Data: Clone> Diagram<data::Selectable<Data>> {
+    /// Highlights the face of the hovered cell and of the selected cells in the previous level.
+    /// With `boundary`, the faces of those cells are highlighted too, down to the tower.
+    ///
+    pub fn highlight_faces(&mut self, hovered: Option<&ViewIndex>, boundary: bool) {
+        self.unhighlight_all();
+
+        let mut cells =
+        self.selected_cells()
+            .map(|sel| sel.as_cells())
+            .unwrap_or_default();
+
+        // NOTE: The hovered cell may be gone since the last edit, which only means that nothing
+        // is hovered until the cursor moves.
+        //
+        if let Some(hovered) = hovered {
+            if self.hover(hovered).is_ok() {
+                cells.push(hovered.clone());
+            }
+        }
+
+        let mut faces = vec![];
+        let mut lower = vec![];
+
+        for cell in &cells {
+            if let Some(Face { ends, fill }) = self.face_of(cell) {
+                if boundary {
+                    lower.extend(ends.iter().cloned());
+                    lower.push(fill.clone());
+                }
+
+                faces.extend(ends.into_iter().map(|end| (end, data::Highlight::End)));
+                faces.push((fill, data::Highlight::Fill));
+            }
+        }
+
+        let mut boundary_cells = HashSet::new();
+
+        while let Some(cell) = lower.pop() {
+            if let Some(Face { ends, fill }) = self.face_of(&cell) {
+                for cell in ends.into_iter().chain(Some(fill)) {
+                    if boundary_cells.insert(cell.clone()) {
+                        lower.push(cell);
+                    }
+                }
+            }
+        }
+
+        // NOTE: Faces go last, so that they win over the boundary of another cell.
+        //
+        for cell in boundary_cells {
+            self.highlight(&cell, data::Highlight::Boundary).unwrap();
+        }
+
+        for (cell, highlight) in faces {
+            self.highlight(&cell, highlight).unwrap();
+        }
+    }
+
+    fn face_of(&self, cell: &ViewIndex) -> Option<Face> {
+        match self.cell(cell) {
+            Ok(super::Cell::Leveled(meta)) => Some(meta.face),
+
+            _ => None,
+        }
+    }
+
+    pub fn retain_selected(&self) -> Result<Option<Tail<data::Selectable<Data>>>, Error> {
+        if let Some(sel) = self.selected_cells_no_prev() {
+            self.check_form_tree(&sel.as_paths())?;
+
+            let keep =
+            sel
+                .as_paths()
+                .into_iter()
+                .flat_map(|path| {
+                    let mut paths =
+                    self.cells
+                        .paths()
+                        .into_iter()
+                        .map(|(inner, _)| inner)
+                        .filter(|inner| inner.len() > path.len() && inner.starts_with(&path))
+                        .collect_vec();
+
+                    paths.push(path);
+
+                    paths
+                })
+                .map(|path| self.to_index(path))
+                .collect_vec();
+
+            let (mut this, _) = self.restrict(&keep)?;
+
+            this.unselect_all(0);
+
+            Ok(Some(Tail::Diagram(Box::new(
+                this
+            ))))
+
+        } else {
+            self.prev.retain_selected()
+        }
+    }
+}
+
+impl<Data: Clone> Diagram<Data> {
+    /// Restricts the opetope to `keep` and, recursively, to the faces of the kept cells in all
+    /// lower levels. Cells are reindexed, kept cells nested in dropped groups are lifted to
+    /// the parent cell space.
+    ///
+    /// Returns the restricted opetope along with the mapping from old to new indices of this level.
+    ///
+    pub fn restrict(&self, keep: &[ViewIndex]) -> Result<(Self, HashMap<ViewIndex, ViewIndex>), Error> {
+        if keep.is_empty() {
+            return Err(Error::CannotRestrictToNothing);
+        }
+
+        let mut paths = vec![];
+
+        for cell in keep {
+            let path = self.valid_level(cell)?;
+
+            self.get(&path)
+                .ok_or_else(|| Error::NoSuchCell(cell.clone()))?;
+
+            paths.push(path);
+        }
+
+        let retained = Retained::collect(self.cells.nodes(), &[], &paths);
+
+        let mut lines = vec![];
+        retained.iter().for_each(|kept| kept.lines(&mut lines));
+
+        let (prev, line_mapping) = self.prev.restrict(&lines)?;
+
+        let remap_line = |line: &ViewIndex|
+            line_mapping
+                .get(line)
+                .cloned()
+                .ok_or_else(|| Error::NoSuchCell(line.clone()));
+
+        let remap = |face: &Face| -> Result<Face, Error> {
+            Ok(Face {
+                ends: face.ends.iter().map(&remap_line).collect::<Result<_, _>>()?,
+                fill: remap_line(&face.fill)?,
+            })
+        };
+
+        let mut mapping = HashMap::new();
+        let mut cells = fill![];
+
+        Retained::build(retained, None, &[], self.level() - 1, &remap, &mut cells, &mut mapping)?;
+        cells.reserve_ids(self.cells.next_id());
+
+        Ok((Self { cells, prev }, mapping))
+    }
+}
+
+
+/// A cell kept by `Diagram::restrict`, along with the kept cells nested in it.
+///
+struct Retained<'c, Data> {
+    path: Vec<TimelessIndex>,
+    cell: &'c MetaCell<Data>,
+    id: u64,
+
+    inner: Vec<Self>,
+}
+
+impl<'c, Data> Retained<'c, Data> {
+    fn collect(nodes: Vec<(TimelessIndex, NodeRef<'c, MetaCell<Data>>)>, prefix: &[TimelessIndex], keep: &[Vec<TimelessIndex>]) -> Vec<Self> {
+        let mut retained = vec![];
+
+        for (index, node) in nodes {
+            let mut path = prefix.to_vec();
+            path.push(index);
+
+            let inner =
+            node.children()
+                .map(|children| Self::collect(children, &path, keep))
+                .unwrap_or_default();
+
+            if keep.contains(&path) {
+                retained.push(Self { path, cell: node.val(), id: node.id(), inner });
+
+            } else {
+                retained.extend(inner);
+            }
+        }
+
+        retained
+    }
+
+    fn lines(&self, lines: &mut Vec<ViewIndex>) {
+        let face = self.cell.face();
+
+        lines.extend(face.ends.iter().cloned());
+        lines.push(face.fill.clone());
+
+        for inner in &self.inner {
+            inner.lines(lines);
+        }
+    }
+}
+
+impl<Data: Clone> Retained<'_, Data> {
+    /// Pushes the kept cells into the contents of `owner`, or to the roots.
+    ///
+    fn build(
+        retained: Vec<Self>,
+        owner: Option<Handle>,
+        prefix: &[TimelessIndex],
+        level: usize,
+        remap: &impl Fn(&Face) -> Result<Face, Error>,
+        cells: &mut TracingTree<MetaCell<Data>>,
+        mapping: &mut HashMap<ViewIndex, ViewIndex>,
+    ) -> Result<(), Error>
+    {
+        for kept in retained {
+            let cell = MetaCell {
+                data: kept.cell.data().clone(),
+                face: remap(kept.cell.face())?,
+            };
+
+            let (index, handle) = cells.push(owner, cell);
+
+            // NOTE: Cells pushed before have their old numbers, which are distinct from this one.
+            //
+            cells.set_id(handle, kept.id);
+
+            let mut path = prefix.to_vec();
+            path.push(index);
+
+            Self::build(kept.inner, Some(handle), &path, level, remap, cells, mapping)?;
+
+            mapping.insert(
+                ViewIndex::Leveled { level, path: kept.path },
+                ViewIndex::Leveled { level, path },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+
+// IMPL: Accessing
+//
+impl<Data> MetaCell<Data> {
+    pub const fn face(&self) -> &Face {
+        &self.face
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.face.ends.len()
+    }
+
+
+    pub const fn data(&self) -> &Data {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut Data {
+        &mut self.data
+    }
+}
+
+impl Face {
+    /// Whether the faces have the same lines, regardless of the order of the ends.
+    ///
+    fn matches(&self, other: &Self) -> bool {
+        let mut ends = self.ends.clone();
+        let mut other_ends = other.ends.clone();
+
+        ends.sort();
+        other_ends.sort();
+
+        self.fill == other.fill && ends == other_ends
+    }
+
+    fn collect<'c>(cells: impl Iterator<Item = &'c Self>) -> Self {
+        let mut ends = vec![];
+        let mut fills = vec![];
+
+        for cell in cells {
+            ends.extend(cell.ends.iter());
+
+            fills.push(&cell.fill);
+        }
+
+        fills.retain(|fill| {
+            if let Some(pos) = ends.iter().position(|end| end == fill) {
+                ends.remove(pos);
+
+                false
+
+            } else {
+                true
+            }
+        });
+
+        if let [fill] = fills[..] {
+            let ends =
+            ends.into_iter()
+                .cloned()
+                .collect();
+
+            let fill = fill.clone();
+
+            Face { ends, fill }
+
+        } else {
+            unreachable![]
+        }
+    }
+}
+
+
+// IMPL: Layout
+//
+impl<Data: Named> Diagram<Data> {
+    /// Positions of the cells and lines as drawn by `Diagram::view`, including all levels below.
+    ///
+    pub fn layout(&self) -> Layout {
+        self.layout_folded(&fill![])
+    }
+
+    /// Lays the opetope out as drawn with its levels folded.
+    ///
+    pub fn layout_folded(&self, levels: &Levels) -> Layout {
+        Layout::new(&self.layout_node(levels))
+    }
+}
+
+impl<Data: Named> Diagram<Data> {
+    pub(in super) fn layout_node(&self, levels: &Levels) -> layout::Node {
+        let level = self.level() - 1;// NOTE: Since `ViewIndex::Leveled` is shifted left.
+
+        let prev = self.prev.layout_node(levels);
+
+        if levels.fold(self.level()) == Fold::Hidden {
+            return prev;
+        }
+
+        let mut cells =
+        self.cells
+            .nodes();
+
+        let mut parts = vec![];
+
+        while !cells.is_empty() {
+            parts.push(CellCoordinator::collect_from(level, vec![], &mut cells).layout());
+        }
+
+        parts.reverse();
+
+        let parts =
+        if parts.is_empty() {
+            layout::Node::Line(LINE_LEN)
+
+        } else {
+            layout::Node::Row { children: parts, spacing: SPACING * 2, align: layout::Align::Start }
+        };
+
+        let parts = layout::Node::level(self.level(), parts, levels);
+
+        layout::Node::Row { children: vec![prev, parts], spacing: SPACING * 3, align: layout::Align::Start }
+    }
+}
+
+
+#[cfg(feature = "gui")]
+pub mod viewing {
+    use std::collections::HashMap;
+
+    use crate::opetope::{
+        tree::NodeMut,
+        utils::routines,
+        viewing::Message,
+
+        diagram::*,
+    };
+
+    use crate::behavior;
+    use crate::model::{ Render, Zoom, Levels };
+
+
+
+    impl<Data> Diagram<data::Selectable<Data>>
+    where Data: behavior::SimpleView {
+        /// Draws the opetope as widgets, following `Diagram::layout_node`.
+        ///
+        pub fn view(&mut self, render: Render, zoom: Zoom, levels: &Levels) -> iced::Element<Message> {
+            let root = self.layout_node(levels);
+
+            let mut cells = HashMap::new();
+
+            self.cells_by_index(&mut cells);
+
+            let view = routines::view_node(&root, &mut cells, render, zoom, levels);
+
+            if cfg![debug_assertions] {
+                view.explain(color![255, 0, 0])
+
+            } else {
+                view
+            }
+        }
+    }
+
+
+    impl<Data> Diagram<Data> {
+        /// Collects the cells of this level and all levels below it.
+        ///
+        pub(in crate::opetope) fn cells_by_index<'t>(&'t mut self, cells: &mut HashMap<ViewIndex, &'t mut Data>) {
+            let level = self.level() - 1;// NOTE: Since `ViewIndex::Leveled` is shifted left.
+
+            Self::collect_space(level, &[], self.cells.nodes_mut(), cells);
+
+            self.prev.cells_by_index(cells);
+        }
+
+        fn collect_space<'t>(
+            level: usize,
+            prefix: &[TimelessIndex],
+            cell_space: Vec<(TimelessIndex, NodeMut<'t, MetaCell<Data>>)>,
+
+            cells: &mut HashMap<ViewIndex, &'t mut Data>,
+        ) {
+            for (index, NodeMut { val, children, .. }) in cell_space {
+                let mut path = prefix.to_vec();
+                path.push(index);
+
+                cells.insert(ViewIndex::Leveled { level, path: path.clone() }, &mut val.data);
+
+                if let Some(children) = children {
+                    Self::collect_space(level, &path, children, cells);
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::{ script::Script, testing::* };
+
+    const BRANCHING: &str = "\
+        extrude 0 as a wrap α
+        extrude a as b wrap β
+        extrude α β as h wrap θ
+        sprout 0 as z wrap w
+        pass h x";
+
+    const CHAIN: &str = "\
+        extrude 0 as a wrap α
+        extrude a as b wrap β
+        extrude b as c wrap γ";
+
+
+    #[test]
+    fn retaining_any_cell_gives_a_valid_opetope() {
+        let diagram = opetope(BRANCHING);
+
+        for index in all_indices(&diagram) {
+            let mut selected = diagram.clone();
+            selected.select(&index).unwrap();
+
+            match selected.retain_selected().unwrap() {
+                Some(Tail::Diagram(retained)) =>
+                    assert_eq![retained.validate(), Ok(()), "keeping {}", index],
+
+                Some(Tail::Tower(_)) =>
+                    assert_eq![index.level(), 0],
+
+                None =>
+                    panic!["nothing retained from {}", index],
+            }
+        }
+    }
+
+    #[test]
+    fn deleting_an_end_cell() {
+        let mut diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β");
+
+        let end = index(&diagram, "β");
+
+        assert_eq![diagram.delete(&end).ok(), Ok(Interaction::Here { action: Action::Delete { cell: end } })];
+
+        assert_eq![names(&diagram, 1), ["α"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_a_tower_cell_deletes_its_wraps() {
+        let mut diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β");
+
+        diagram.delete(&index(&diagram, "b")).unwrap();
+
+        assert_eq![names(&diagram, 0), ["0", "a"]];
+        assert_eq![names(&diagram, 1), ["α"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_a_group_dissolves_it() {
+        let mut diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β\nextrude α β as h wrap θ");
+
+        let interaction = diagram.delete(&index(&diagram, "h")).unwrap();
+
+        assert![matches![interaction, Interaction::Here { action: Action::Ungroup { .. } }]];
+
+        assert_eq![names(&diagram, 1), ["α", "β"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_a_referenced_cell_fails() {
+        // NOTE: Nested in a group, `x` is no longer just a wrap of `h`.
+        //
+        let mut diagram = opetope(&format!["{}\nextrude x as y wrap ψ", BRANCHING]);
+
+        let h = index(&diagram, "h");
+        let users = vec![index(&diagram, "y"), index(&diagram, "x")];
+
+        assert_eq![diagram.delete(&h).ok(), Err(Error::CellIsReferenced(h, users))];
+        assert_eq![names(&diagram, 1), ["w", "h", "α", "β"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_a_connecting_cell_fails() {
+        let mut diagram = opetope(CHAIN);
+
+        let middle = index(&diagram, "β");
+
+        assert_eq![diagram.delete(&middle).ok(), Err(Error::NotAnEnd(middle))];
+        assert_eq![names(&diagram, 1), ["α", "β", "γ"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_the_only_cell_of_a_level_fails() {
+        let mut diagram = opetope("extrude 0 as a wrap α");
+
+        let only = index(&diagram, "α");
+
+        assert_eq![diagram.delete(&only).ok(), Err(Error::CannotDeleteOnlyCell(only))];
+        assert_eq![names(&diagram, 1), ["α"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_a_tower_cell_with_every_cell_as_its_wrap_fails() {
+        let mut diagram = opetope("extrude 0 as a wrap α");
+
+        let a = index(&diagram, "a");
+
+        assert_eq![diagram.delete(&a).ok(), Err(Error::CannotDeleteOnlyCell(a))];
+        assert_eq![names(&diagram, 0), ["0", "a"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_the_ends_of_a_chain() {
+        let mut diagram = opetope(CHAIN);
+
+        diagram.delete(&index(&diagram, "α")).unwrap();
+        diagram.delete(&index(&diagram, "γ")).unwrap();
+
+        assert_eq![names(&diagram, 1), ["β"]];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn deleting_from_a_group_fails() {
+        let mut diagram = opetope(BRANCHING);
+
+        let nested = index(&diagram, "α");
+
+        assert_eq![diagram.delete(&nested).ok(), Err(Error::CannotDeleteFromGroup(nested))];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    fn selected(diagram: &Opetope) -> Vec<String> {
+        diagram
+            .selected_cells()
+            .map(|selection| selection.as_cells())
+            .unwrap_or_default()
+            .iter()
+            .map(|cell| diagram.cell(cell).unwrap().data().name())
+            .collect()
+    }
+
+
+    #[test]
+    fn range_selection_keeps_the_anchor() {
+        let mut diagram = opetope(CHAIN);
+
+        diagram.select(&index(&diagram, "α")).unwrap();
+        diagram.select_range(&index(&diagram, "γ")).unwrap();
+
+        assert_eq![selected(&diagram), ["α", "β", "γ"]];
+    }
+
+    #[test]
+    fn selecting_all_keeps_the_selected_cells() {
+        let mut diagram = opetope(CHAIN);
+
+        diagram.select(&index(&diagram, "β")).unwrap();
+        diagram.select_all();
+
+        assert_eq![selected(&diagram), ["α", "β", "γ"]];
+    }
+
+    #[test]
+    fn selecting_all_keeps_the_tower_selection() {
+        let mut diagram = opetope(CHAIN);
+
+        diagram.select(&index(&diagram, "b")).unwrap();
+        diagram.select_all();
+
+        assert_eq![selected(&diagram), ["b"]];
+    }
+
+    #[test]
+    fn grouping_and_ungrouping_keep_the_ids() {
+        let mut diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β");
+
+        let ids = |diagram: &Opetope| -> Vec<_> {
+            ["0", "a", "b", "α", "β"]
+                .iter()
+                .map(|name| diagram.id_of(&index(diagram, name)).unwrap())
+                .collect()
+        };
+
+        let before = ids(&diagram);
+
+        Script::parse("extrude α β as h wrap θ").unwrap().run(&mut diagram).unwrap();
+
+        assert_eq![ids(&diagram), before];
+
+        diagram.ungroup(&index(&diagram, "h")).unwrap();
+
+        assert_eq![ids(&diagram), before];
+        assert_eq![diagram.validate(), Ok(())];
+    }
+
+    #[test]
+    fn restricting_to_nothing_fails() {
+        let diagram = opetope(BRANCHING);
+
+        assert_eq![diagram.restrict(&[]).map(|_| ()), Err(Error::CannotRestrictToNothing)];
+    }
+}
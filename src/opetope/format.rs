@@ -0,0 +1,777 @@
+use std::fmt;
+use std::iter;
+use std::collections::{ HashMap, HashSet };
+
+use tracing_vec::*;
+use serde::{ Serialize, Deserialize };
+
+use super::{ data, journal::Journal, tree, Diagram, Tail, Tower, MetaCell, TracingTree, Handle, ViewIndex, CellId };
+
+
+
+pub const FORMAT: &str = "eru";
+pub const VERSION: u64 = 1;
+
+
+/// On-disk representation of an opetope, independent of the in-memory structures.
+///
+/// Level 0 is the tower, listed from the innermost cell. Cells of the other levels are numbered
+/// in the order they appear in the file (a group precedes its contents), faces refer to these
+/// numbers in the previous level.
+///
+/// The journal of edits which built the opetope is optional, files written without it are still
+/// read by the same version.
+///
+/// So are the identifiers of cells, see `CellId`: cells saved without them are given new ones when
+/// the file is read. The identifiers of the tower are listed apart from the names, in the same
+/// order. Each level also keeps the identifier to be given to its next cell, so that the
+/// identifiers of deleted cells aren't given again.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct File {
+    pub format: String,
+    pub version: u64,
+
+    pub tower: Vec<String>,
+    pub levels: Vec<Level>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tower_ids: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tower_next_id: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal: Option<Journal>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Level {
+    pub cells: Vec<Cell>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cell {
+    pub name: String,
+    pub face: Face,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inner: Vec<Cell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Face {
+    pub source: Vec<usize>,
+    pub target: usize,
+}
+
+/// The fields every version of the format starts with, read before the rest of the file so that
+/// a file of a newer version is reported as such rather than as malformed.
+///
+#[derive(Deserialize)]
+struct Header {
+    format: String,
+    version: u64,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Syntax(String),
+
+    UnknownFormat(String),
+    NewerVersion(u64),
+
+    EmptyTower,
+    NoLevels,
+    NoSuchLine { level: usize, line: usize },
+    DuplicateId(CellId),
+
+    Opetope(super::Error),
+}
+impl From<super::Error> for Error {
+    fn from(e: super::Error) -> Self {
+        Self::Opetope(e)
+    }
+}
+
+
+/// Cell data which is stored by its name.
+///
+pub trait Named {
+    fn name(&self) -> String;
+
+    fn from_name(name: String) -> Self;
+}
+
+impl Named for String {
+    fn name(&self) -> String {
+        self.clone()
+    }
+
+    fn from_name(name: String) -> Self {
+        name
+    }
+}
+
+impl<Data: Named> Named for data::Selectable<Data> {
+    fn name(&self) -> String {
+        self.inner().name()
+    }
+
+    fn from_name(name: String) -> Self {
+        Data::from_name(name).into()
+    }
+}
+
+
+
+/// Serializes the opetope in the current format version.
+///
+pub fn to_string<Data: Named>(diagram: &Diagram<Data>) -> Result<String, Error> {
+    write_file(&File::new(diagram)?)
+}
+
+/// Serializes the opetope together with the journal of edits which built it.
+///
+pub fn to_string_with_journal<Data: Named>(diagram: &Diagram<Data>, journal: &Journal) -> Result<String, Error> {
+    let mut file = File::new(diagram)?;
+    file.journal = Some(journal.clone());
+
+    write_file(&file)
+}
+
+/// Deserializes an opetope saved in any format version up to the current one, including the
+/// unversioned format of earlier releases.
+///
+pub fn from_str<Data: Named>(s: &str) -> Result<Diagram<Data>, Error> {
+    read_file(s)?.into_diagram()
+}
+
+/// Deserializes an opetope along with its journal.
+///
+/// The opetope is the one reached by replaying the journal, so that the cells referenced by
+/// further entries stay valid. If the file has no journal, or the journal doesn't lead to the saved
+/// opetope, a new journal is started from the saved opetope.
+///
+pub fn from_str_with_journal<Data: Clone + Named>(s: &str) -> Result<(Diagram<Data>, Journal), Error> {
+    let mut file = read_file(s)?;
+    let journal = file.journal.take();
+
+    if let Some(journal) = journal {
+        if let Ok(Some(diagram)) = journal.replay().map(|states| states.last().cloned()) {
+            let mut replayed = File::new(&diagram).ok();
+
+            // NOTE: Files saved before cells had identifiers are compared without them.
+            //
+            if file.tower_next_id.is_none() {
+                replayed = replayed.map(File::without_ids);
+            }
+
+            if replayed.as_ref() == Some(&file) {
+                return Ok((diagram, journal));
+            }
+        }
+    }
+
+    file.into_diagram().and_then(Journal::started)
+}
+
+
+fn write_file(file: &File) -> Result<String, Error> {
+    serde_json::to_string_pretty(file)
+        .map_err(|e| Error::Syntax(e.to_string()))
+}
+
+fn read_file(s: &str) -> Result<File, Error> {
+    let value: serde_json::Value =
+    serde_json::from_str(s)
+        .map_err(|e| Error::Syntax(e.to_string()))?;
+
+    if value.get("version").is_some() {
+        Header::deserialize(&value)
+            .map_err(|e| Error::Syntax(e.to_string()))?
+            .check()?;
+
+        serde_json::from_value::<File>(value)
+            .map_err(|e| Error::Syntax(e.to_string()))
+
+    } else {
+        serde_json::from_value::<legacy::Diagram>(value)
+            .map_err(|e| Error::Syntax(e.to_string()))?
+            .into_file()
+    }
+}
+
+
+// IMPL: Writing
+//
+impl File {
+    pub fn new<Data: Named>(diagram: &Diagram<Data>) -> Result<Self, Error> {
+        let mut tower = vec![];
+        let mut tower_ids = vec![];
+        let mut levels = vec![];
+
+        let tower_next_id = write_diagram(diagram, &mut tower, &mut tower_ids, &mut levels)?;
+
+        Ok(Self {
+            format: FORMAT.to_string(),
+            version: VERSION,
+
+            tower,
+            levels,
+
+            tower_ids,
+            tower_next_id: Some(tower_next_id),
+
+            journal: None,
+        })
+    }
+
+    fn without_ids(mut self) -> Self {
+        fn strip(cells: &mut [Cell]) {
+            for cell in cells {
+                cell.id = None;
+
+                strip(&mut cell.inner);
+            }
+        }
+
+        self.tower_ids.clear();
+        self.tower_next_id = None;
+
+        for level in &mut self.levels {
+            level.next_id = None;
+
+            strip(&mut level.cells);
+        }
+
+        self
+    }
+}
+
+// IMPL: Reading
+//
+impl File {
+    pub fn check(&self) -> Result<(), Error> {
+        Header { format: self.format.clone(), version: self.version }.check()
+    }
+
+    pub fn into_diagram<Data: Named>(self) -> Result<Diagram<Data>, Error> {
+        self.check()?;
+
+        let Self { tower, levels, tower_ids, tower_next_id, .. } = self;
+
+        if tower.is_empty() {
+            return Err(Error::EmptyTower);
+        }
+
+        let mut numbers = HashSet::new();
+
+        for &number in &tower_ids {
+            if !numbers.insert(number) {
+                return Err(Error::DuplicateId(CellId { level: 0, number }));
+            }
+        }
+
+        let tower =
+        Tower::from_cells(
+            tower
+                .into_iter()
+                .map(Data::from_name)
+                .zip(tower_ids.into_iter().map(Some).chain(iter::repeat(None)))
+                .collect(),
+
+            tower_next_id.unwrap_or(0),
+        );
+
+        let mut lines =
+        tower
+            .cells()
+            .timeless_indices()
+            .map(ViewIndex::Ground)
+            .collect::<Vec<_>>();
+
+        let mut tail = Tail::Tower(tower);
+
+        for (level, Level { cells, next_id }) in levels.into_iter().enumerate() {
+            let mut numbers = vec![];
+            let mut tree: TracingTree<_> = fill![];
+
+            // NOTE: Cells without a number are numbered after all the given ones, as in the tower.
+            //
+            let last_id = Cell::last_id(&cells);
+
+            tree.reserve_ids(next_id.unwrap_or(0).max(last_id.map_or(0, |id| id + 1)));
+
+            read_cells(cells, None, &[], level, &lines, &mut tree, &mut numbers)?;
+
+            tail = Tail::Diagram(Box::new(Diagram::from_parts(tail, tree)));
+            lines = numbers;
+        }
+
+        match tail {
+            Tail::Diagram(diagram) => Ok(*diagram),
+            Tail::Tower(_) => Err(Error::NoLevels),
+        }
+    }
+}
+
+impl Cell {
+    /// The greatest number given to the cells or their contents.
+    ///
+    fn last_id(cells: &[Self]) -> Option<u64> {
+        cells
+            .iter()
+            .flat_map(|cell| cell.id.into_iter().chain(Self::last_id(&cell.inner)))
+            .max()
+    }
+}
+
+
+impl Header {
+    fn check(&self) -> Result<(), Error> {
+        if self.format != FORMAT {
+            Err(Error::UnknownFormat(self.format.clone()))
+
+        } else if self.version > VERSION {
+            Err(Error::NewerVersion(self.version))
+
+        } else {
+            Ok(())
+        }
+    }
+}
+
+
+/// A cell of a cell space which can be written to a file.
+///
+trait Node: Sized {
+    fn name(&self) -> String;
+    fn face(&self) -> (&[ViewIndex], &ViewIndex);
+    fn content(&self) -> Option<Vec<(TimelessIndex, Self)>>;
+    fn id(&self) -> Option<u64>;
+}
+
+impl<'t, Data: Named> Node for tree::NodeRef<'t, MetaCell<Data>> {
+    fn name(&self) -> String {
+        self.val().data().name()
+    }
+
+    fn face(&self) -> (&[ViewIndex], &ViewIndex) {
+        let face = self.val().face();
+
+        (&face.ends, &face.fill)
+    }
+
+    fn content(&self) -> Option<Vec<(TimelessIndex, Self)>> {
+        self.children()
+    }
+
+    fn id(&self) -> Option<u64> {
+        Some(tree::NodeRef::id(self))
+    }
+}
+
+
+/// Returns the identifier to be given to the next cell of the tower.
+///
+fn write_diagram<Data: Named>(
+    diagram: &Diagram<Data>,
+    tower: &mut Vec<String>,
+    tower_ids: &mut Vec<u64>,
+    levels: &mut Vec<Level>,
+) -> Result<u64, Error>
+{
+    write_level(diagram, tower, tower_ids, levels)
+        .map(|(next_id, _)| next_id)
+}
+
+fn write_level<Data: Named>(
+    diagram: &Diagram<Data>,
+    tower: &mut Vec<String>,
+    tower_ids: &mut Vec<u64>,
+    levels: &mut Vec<Level>,
+) -> Result<(u64, HashMap<ViewIndex, usize>), Error>
+{
+    let (tower_next_id, lines) =
+    match diagram.prev() {
+        Tail::Tower(t) => {
+            tower_ids.extend(t.ids().into_iter().map(|(id, _)| id.number));
+
+            (t.next_id(), write_tower(t.cells(), Named::name, tower))
+        },
+
+        Tail::Diagram(d) => write_level(d, tower, tower_ids, levels)?,
+    };
+
+    let mut numbers = HashMap::new();
+
+    let cells = write_cells(diagram.cells().nodes(), &[], diagram.level() - 1, &lines, &mut numbers)?;
+    levels.push(Level { cells, next_id: Some(diagram.cells().next_id()) });
+
+    Ok((tower_next_id, numbers))
+}
+
+fn write_tower<D>(cells: &TracingVec<D>, name: impl Fn(&D) -> String, tower: &mut Vec<String>) -> HashMap<ViewIndex, usize> {
+    cells
+        .iter_timeless_indices()
+        .enumerate()
+        .map(|(number, (index, data))| {
+            tower.push(name(data));
+
+            (ViewIndex::Ground(index), number)
+        })
+        .collect()
+}
+
+fn write_cells<N: Node>(
+    cell_space: Vec<(TimelessIndex, N)>,
+    prefix: &[TimelessIndex],
+    level: usize,
+    lines: &HashMap<ViewIndex, usize>,
+    numbers: &mut HashMap<ViewIndex, usize>,
+) -> Result<Vec<Cell>, Error>
+{
+    let number = |line: &ViewIndex|
+        lines
+            .get(line)
+            .copied()
+            .ok_or_else(|| Error::Opetope(super::Error::NoSuchCell(line.clone())));
+
+    let mut cells = vec![];
+
+    for (index, cell) in cell_space {
+        let mut path = prefix.to_vec();
+        path.push(index);
+
+        // NOTE: Numbered before the contents.
+        numbers.insert(ViewIndex::Leveled { level, path: path.clone() }, numbers.len());
+
+        let (ends, fill) = cell.face();
+
+        let face = Face {
+            source: ends.iter().map(&number).collect::<Result<_, _>>()?,
+            target: number(fill)?,
+        };
+
+        let inner =
+        if let Some(content) = cell.content() {
+            write_cells(content, &path, level, lines, numbers)?
+
+        } else {
+            vec![]
+        };
+
+        cells.push(Cell { name: cell.name(), face, id: cell.id(), inner });
+    }
+
+    Ok(cells)
+}
+
+/// Pushes the cells into the contents of `owner`, or to the roots.
+///
+fn read_cells<Data: Named>(
+    cells: Vec<Cell>,
+    owner: Option<Handle>,
+    prefix: &[TimelessIndex],
+    level: usize,
+    lines: &[ViewIndex],
+    tree: &mut TracingTree<MetaCell<Data>>,
+    numbers: &mut Vec<ViewIndex>,
+) -> Result<(), Error>
+{
+    let line = |number: &usize|
+        lines
+            .get(*number)
+            .cloned()
+            .ok_or(Error::NoSuchLine { level, line: *number });
+
+    for Cell { name, face, id, inner } in cells {
+        let cell = MetaCell {
+            data: Data::from_name(name),
+            face: super::Face {
+                ends: face.source.iter().map(&line).collect::<Result<_, _>>()?,
+                fill: line(&face.target)?,
+            },
+        };
+
+        let (index, handle) =
+        match id {
+            Some(number) =>
+                tree.push_numbered(owner, cell, number)
+                    .ok_or(Error::DuplicateId(CellId { level: level + 1, number }))?,
+
+            None => tree.push(owner, cell),
+        };
+
+        let mut path = prefix.to_vec();
+        path.push(index);
+
+        // NOTE: Numbered before the contents.
+        numbers.push(ViewIndex::Leveled { level, path: path.clone() });
+
+        read_cells(inner, Some(handle), &path, level, lines, tree, numbers)?;
+    }
+
+    Ok(())
+}
+
+
+/// The unversioned format, which was derived from the in-memory structures.
+///
+mod legacy {
+    use super::*;
+
+
+    #[derive(Deserialize)]
+    pub struct Diagram {
+        cells: TracingVec<Cell>,
+        prev: Tail,
+    }
+
+    #[derive(Deserialize)]
+    enum Tail {
+        Tower(Tower),
+        Diagram(Box<Diagram>),
+    }
+
+    #[derive(Deserialize)]
+    struct Tower {
+        cells: TracingVec<Selectable>,
+    }
+
+    #[derive(Deserialize)]
+    pub(in super) struct Cell {
+        meta: MetaCell,
+        content: Option<TracingVec<Self>>,
+    }
+
+    #[derive(Deserialize)]
+    struct MetaCell {
+        data: Selectable,
+        face: Face,
+    }
+
+    #[derive(Deserialize)]
+    struct Face {
+        ends: Vec<ViewIndex>,
+        fill: ViewIndex,
+    }
+
+    #[derive(Deserialize)]
+    struct Selectable {
+        val: String,
+    }
+
+
+    impl Diagram {
+        pub fn into_file(self) -> Result<File, Error> {
+            let mut tower = vec![];
+            let mut levels = vec![];
+
+            self.write(&mut tower, &mut levels)?;
+
+            Ok(File {
+                format: FORMAT.to_string(),
+                version: VERSION,
+
+                tower,
+                levels,
+
+                tower_ids: vec![],
+                tower_next_id: None,
+
+                journal: None,
+            })
+        }
+
+        fn write(&self, tower: &mut Vec<String>, levels: &mut Vec<Level>) -> Result<(usize, HashMap<ViewIndex, usize>), Error> {
+            let (prev_level, lines) =
+            match &self.prev {
+                Tail::Tower(t) => (0, write_tower(&t.cells, |data| data.val.clone(), tower)),
+                Tail::Diagram(d) => d.write(tower, levels)?,
+            };
+
+            let mut numbers = HashMap::new();
+
+            let cells = write_cells(self.cells.iter_timeless_indices().collect(), &[], prev_level, &lines, &mut numbers)?;
+            levels.push(Level { cells, next_id: None });
+
+            Ok((prev_level + 1, numbers))
+        }
+    }
+
+    impl<'c> Node for &'c Cell {
+        fn name(&self) -> String {
+            self.meta.data.val.clone()
+        }
+
+        fn face(&self) -> (&[ViewIndex], &ViewIndex) {
+            (&self.meta.face.ends, &self.meta.face.fill)
+        }
+
+        fn content(&self) -> Option<Vec<(TimelessIndex, Self)>> {
+            let cell: &'c Cell = self;
+
+            cell.content
+                .as_ref()
+                .map(|content| content.iter_timeless_indices().collect())
+        }
+
+        fn id(&self) -> Option<u64> {
+            None
+        }
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(e) =>
+                write![fmt, "{}", e],
+
+            Self::UnknownFormat(format) =>
+                write![fmt, "Not an opetope file (format `{}`)", format],
+
+            Self::NewerVersion(version) =>
+                write![fmt, "File format version {} is newer than the supported version {}, please update eru", version, VERSION],
+
+            Self::EmptyTower =>
+                write![fmt, "The tower has no cells"],
+
+            Self::NoLevels =>
+                write![fmt, "There are no levels above the tower"],
+
+            Self::NoSuchLine { level, line } =>
+                write![fmt, "Face refers to cell {} of level {}, which does not exist", line, level],
+
+            Self::DuplicateId(id) =>
+                write![fmt, "Cell id {} is used more than once", id],
+
+            Self::Opetope(e) =>
+                write![fmt, "{}", e],
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::testing::*;
+
+    const LEGACY: &str = r#"{
+        "cells": {
+            "mem": [{
+                "val": {
+                    "meta": {
+                        "data": { "val": "α", "selected": false },
+                        "face": { "ends": [{ "Ground": { "pos": 0 } }], "fill": { "Ground": { "pos": 1 } } }
+                    },
+                    "content": null
+                },
+                "birth": 0
+            }],
+            "snapshots": [[0]]
+        },
+        "prev": {
+            "Tower": {
+                "cells": {
+                    "mem": [
+                        { "val": { "val": "x", "selected": false }, "birth": 0 },
+                        { "val": { "val": "y", "selected": false }, "birth": 0 }
+                    ],
+                    "snapshots": [[0, 1]]
+                }
+            }
+        }
+    }"#;
+
+
+    #[test]
+    fn saved_opetope_reads_back_the_same() {
+        let diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β\nextrude α β as h wrap θ\npass h x");
+
+        let read: Opetope = from_str(&to_string(&diagram).unwrap()).unwrap();
+
+        assert_eq![File::new(&read), File::new(&diagram)];
+        assert_eq![read.validate(), Ok(())];
+    }
+
+    #[test]
+    fn ids_are_saved_along_with_the_cells() {
+        let mut diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β\nextrude b as c wrap γ");
+
+        // NOTE: Deleting the first cell leaves a gap in the numbers, which must not be closed.
+        //
+        diagram.delete(&index(&diagram, "α")).unwrap();
+
+        assert_eq![diagram.validate(), Ok(())];
+
+        let read: Opetope = from_str(&to_string(&diagram).unwrap()).unwrap();
+
+        assert_eq![File::new(&read), File::new(&diagram)];
+        assert_eq![read.validate(), Ok(())];
+
+        let ids = |diagram: &Opetope| diagram.ids().into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+
+        assert_eq![ids(&read), ids(&diagram)];
+        assert_eq![read.id_of(&index(&read, "β")), Ok(CellId { level: 1, number: 1 })];
+        assert_eq![read.id_of(&index(&read, "γ")), Ok(CellId { level: 1, number: 2 })];
+    }
+
+    #[test]
+    fn cells_without_ids_are_numbered_after_the_saved_ones() {
+        let mut file = File::new(&opetope("extrude 0 as a wrap α\nextrude a as b wrap β")).unwrap();
+
+        for cell in &mut file.levels[0].cells {
+            cell.id = if cell.name == "β" { Some(0) } else { None };
+        }
+
+        let read: Opetope = file.into_diagram().unwrap();
+
+        assert_eq![read.id_of(&index(&read, "β")), Ok(CellId { level: 1, number: 0 })];
+        assert_eq![read.id_of(&index(&read, "α")), Ok(CellId { level: 1, number: 2 })];
+
+        assert_eq![File::new(&read).unwrap().levels[0].next_id, Some(3)];
+    }
+
+    #[test]
+    fn newer_version_is_reported_before_the_contents() {
+        let newer = format!["{{ \"format\": \"eru\", \"version\": {}, \"cells\": 3 }}", VERSION + 1];
+
+        assert_eq![from_str::<String>(&newer).map(|_| ()), Err(Error::NewerVersion(VERSION + 1))];
+    }
+
+    #[test]
+    fn other_formats_are_rejected() {
+        let other = "{ \"format\": \"svg\", \"version\": 1, \"tower\": [], \"levels\": [] }";
+
+        assert_eq![from_str::<String>(other).map(|_| ()), Err(Error::UnknownFormat("svg".to_string()))];
+    }
+
+    #[test]
+    fn legacy_file_is_migrated() {
+        let file = read_file(LEGACY).unwrap();
+
+        assert_eq![file.tower, ["x", "y"]];
+        assert_eq![
+            file.levels,
+            [Level {
+                cells: vec![Cell { name: "α".to_string(), face: Face { source: vec![0], target: 1 }, id: None, inner: vec![] }],
+                next_id: None,
+            }],
+        ];
+
+        let diagram: Opetope = from_str(LEGACY).unwrap();
+
+        assert_eq![diagram.validate(), Ok(())];
+    }
+}
@@ -0,0 +1,202 @@
+use std::fmt;
+
+use serde::{ Serialize, Deserialize };
+
+use super::{ Diagram, Interaction, format::{ self, File, Named }, script::{ self, Command } };
+
+
+
+/// The edits which built an opetope, starting from a saved snapshot.
+///
+/// Each entry is the command as it would be written in a script, along with the interactions the
+/// edit reported, so that the construction can be inspected, audited or replayed step by step.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Journal {
+    base: Box<File>,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub command: Command,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interactions: Vec<Interaction>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    Base(format::Error),
+    Entry(script::Error),
+}
+
+
+
+impl Journal {
+    /// Starts an empty journal from the opetope.
+    ///
+    pub fn new<Data: Named>(diagram: &Diagram<Data>) -> Result<Self, format::Error> {
+        Ok(Self {
+            base: Box::new(File::new(diagram)?),
+            entries: vec![],
+        })
+    }
+
+    /// Starts an empty journal from the opetope, and returns the opetope as the journal sees it.
+    ///
+    /// Further entries refer to cells by their indices, which are only reproduced by a replay if
+    /// the edits start from the base itself.
+    ///
+    pub fn started<Data: Named>(diagram: Diagram<Data>) -> Result<(Diagram<Data>, Self), format::Error> {
+        let journal = Self::new(&diagram)?;
+
+        Ok((journal.start()?, journal))
+    }
+
+    /// The opetope before the first entry.
+    ///
+    pub fn start<Data: Named>(&self) -> Result<Diagram<Data>, format::Error> {
+        self.base.as_ref().clone().into_diagram()
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn record(&mut self, entries: impl IntoIterator<Item = Entry>) {
+        self.entries.extend(entries);
+    }
+
+    /// Every state of the opetope, from the base to the result of the last entry.
+    ///
+    pub fn replay<Data: Clone + Named>(&self) -> Result<Vec<Diagram<Data>>, Error> {
+        let mut diagram = self.start().map_err(Error::Base)?;
+        let mut states = vec![diagram.clone()];
+
+        for (number, entry) in self.entries.iter().enumerate() {
+            entry.command
+                .apply(&mut diagram)
+                .map_err(|reason|
+                    Error::Entry(script::Error {
+                        line: number + 1,
+                        command: entry.command.to_string(),
+
+                        reason,
+                    })
+                )?;
+
+            states.push(diagram.clone());
+        }
+
+        Ok(states)
+    }
+}
+
+impl Entry {
+    pub fn new(command: Command, interactions: Vec<Interaction>) -> Self {
+        Self { command, interactions }
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Base(e) =>
+                write![fmt, "Journal base: {}", e],
+
+            Self::Entry(e) =>
+                write![fmt, "Journal, {}", e],
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::{ data::Selectable, script::{ Reason, Script }, testing::* };
+
+    fn commands(script: &str) -> Vec<Command> {
+        Script::parse(script)
+            .unwrap()
+            .commands()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn entries_are_recorded_in_order() {
+        let (mut diagram, mut journal) = Journal::started(opetope("")).unwrap();
+
+        for line in ["extrude 0 as a wrap α", "rename a b"] {
+            journal.record(Script::parse(line).unwrap().run(&mut diagram).unwrap());
+        }
+
+        let recorded: Vec<_> =
+        journal
+            .entries()
+            .iter()
+            .map(|entry| entry.command.to_string())
+            .collect();
+
+        assert_eq![recorded, ["extrude 0 as a wrap α", "rename a b"]];
+        assert_eq![journal.entries()[0].interactions.len(), 1];
+    }
+
+    #[test]
+    fn replay_reproduces_every_state() {
+        let (mut diagram, mut journal) = Journal::started(opetope("")).unwrap();
+        let mut states = vec![File::new(&diagram).unwrap()];
+
+        for line in ["extrude 0 as a wrap α", "extrude a as b wrap β", "rename b c"] {
+            journal.record(Script::parse(line).unwrap().run(&mut diagram).unwrap());
+
+            states.push(File::new(&diagram).unwrap());
+        }
+
+        let replayed: Vec<_> =
+        journal
+            .replay::<Selectable<String>>()
+            .unwrap()
+            .iter()
+            .map(|state| File::new(state).unwrap())
+            .collect();
+
+        assert_eq![replayed, states];
+    }
+
+    #[test]
+    fn replay_reports_the_failing_entry() {
+        let mut journal = Journal::new(&opetope("")).unwrap();
+
+        journal.record(
+            commands("extrude 0 as a wrap α\ndelete x\nrename a b")
+                .into_iter()
+                .map(|command| Entry::new(command, vec![]))
+        );
+
+        let e = journal.replay::<Selectable<String>>().unwrap_err();
+
+        assert_eq![
+            e,
+            Error::Entry(script::Error {
+                line: 2,
+                command: "delete x".to_string(),
+
+                reason: Reason::NoSuchCell("x".to_string()),
+            })
+        ];
+    }
+}
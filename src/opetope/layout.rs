@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+
+use super::{ ViewIndex, data::DATA_HEIGHT, viewing::Index };
+
+use crate::model::{ Fold, Levels, zoom };
+use crate::styles::container::{ PADDING, LINE_WIDTH };
+
+
+
+/// The drawing of an opetope, from which `Diagram::view` builds its widgets and `Layout` places
+/// the shapes drawn on the canvas or exported.
+///
+/// Sizes and alignment follow the iced widgets used on screen, so that an exported picture
+/// matches the window.
+///
+#[derive(Debug, Clone)]
+pub enum Node {
+    Space { width: u16, height: u16 },
+    Line(u16),
+
+    Column(Vec<Self>),
+    Row { children: Vec<Self>, spacing: u16, align: Align },
+
+    Container { child: Box<Self>, width: Option<u16>, padding: u16 },
+
+    Cell { index: ViewIndex, label: String, width: u16, content: Option<Box<Self>> },
+
+    /// All cells of a level, drawn at `scale` relative to the rest.
+    ///
+    Level { level: usize, scale: f32, child: Box<Self> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+}
+
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Line(Rect),
+
+    /// `bounds` contain the nested cells, the label is centered in `data`.
+    ///
+    Cell { index: ViewIndex, label: String, bounds: Rect, data: Rect },
+}
+
+/// Where a cell is drawn, along with its place among the groups of its level.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Level of the cell, where the tower is level 0.
+    ///
+    pub level: usize,
+
+    pub bounds: Rect,
+    pub data: Rect,
+
+    /// The group containing the cell.
+    ///
+    pub parent: Option<ViewIndex>,
+    /// Cells contained in the group, in drawing order.
+    ///
+    pub children: Vec<ViewIndex>,
+}
+
+/// Absolutely positioned shapes, in drawing order (groups precede their contents), along with
+/// the frames of the cells and the columns of the levels.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub width: f32,
+    pub height: f32,
+
+    pub shapes: Vec<Shape>,
+
+    frames: HashMap<ViewIndex, Frame>,
+    columns: HashMap<usize, Rect>,
+}
+
+
+
+// IMPL: Initialization
+//
+impl Node {
+    /// Pads the node on both sides.
+    ///
+    pub fn pad(node: Self) -> Self {
+        let padder = || Self::Space { width: 0, height: 0 };
+
+        Self::Row {
+            children: vec![padder(), node, padder()],
+            spacing: PADDING,
+            align: Align::Start,
+        }
+    }
+
+    pub fn container(child: Self, width: Option<u16>, padding: u16) -> Self {
+        Self::Container {
+            child: Box::new(child),
+
+            width,
+            padding,
+        }
+    }
+}
+
+impl Node {
+    /// Wraps the cells of a level, as folded by `levels`.
+    ///
+    pub fn level(level: usize, child: Self, levels: &Levels) -> Self {
+        let scale =
+        match levels.fold(level) {
+            Fold::Shown => 1.,
+            Fold::Thumbnail => zoom::THUMBNAIL,
+            Fold::Hidden => return Self::Space { width: 0, height: 0 },
+        };
+
+        Self::Level { level, scale, child: Box::new(child) }
+    }
+}
+
+impl Layout {
+    pub fn new(root: &Node) -> Self {
+        let (width, height) = root.size();
+
+        let mut layout = Self {
+            width,
+            height,
+
+            shapes: vec![],
+
+            frames: HashMap::new(),
+            columns: HashMap::new(),
+        };
+
+        root.place(0., 0., 1., &mut layout);
+
+        let nesting: Vec<_> =
+        layout.shapes
+            .iter()
+            .filter_map(|shape|
+                match shape {
+                    Shape::Cell { index, .. } =>
+                        layout.frames[index]
+                            .parent
+                            .clone()
+                            .map(|parent| (parent, index.clone())),
+
+                    Shape::Line(_) => None,
+                }
+            )
+            .collect();
+
+        for (parent, child) in nesting {
+            if let Some(frame) = layout.frames.get_mut(&parent) {
+                frame.children.push(child);
+            }
+        }
+
+        layout
+    }
+}
+
+// IMPL: Measuring
+//
+impl Node {
+    pub fn size(&self) -> (f32, f32) {
+        self.size_at(1.)
+    }
+
+    /// Size of the node with all its lengths multiplied by `scale`.
+    ///
+    fn size_at(&self, scale: f32) -> (f32, f32) {
+        let len = |length: u16| length as f32 * scale;
+
+        match self {
+            Self::Space { width, height } =>
+                (len(*width), len(*height)),
+
+            Self::Line(height) =>
+                (len(LINE_WIDTH), len(*height)),
+
+            Self::Column(children) =>
+                children
+                    .iter()
+                    .map(|child| child.size_at(scale))
+                    .fold((0., 0.), |(w, h), (cw, ch)| (f32::max(w, cw), h + ch)),
+
+            Self::Row { children, spacing, .. } => {
+                let spaces = children.len().saturating_sub(1) as f32 * len(*spacing);
+
+                children
+                    .iter()
+                    .map(|child| child.size_at(scale))
+                    .fold((spaces, 0.), |(w, h), (cw, ch)| (w + cw, f32::max(h, ch)))
+            },
+
+            Self::Container { child, width, padding } => {
+                let (cw, ch) = child.size_at(scale);
+                let padding = 2. * len(*padding);
+
+                (
+                    width.map(len).unwrap_or(cw + padding),
+                    ch + padding,
+                )
+            },
+
+            Self::Cell { width, content, .. } => {
+                let (cw, ch) =
+                content
+                    .as_ref()
+                    .map(|content| content.size_at(scale))
+                    .unwrap_or((0., 0.));
+
+                (f32::max(cw, len(*width)), ch + len(DATA_HEIGHT))
+            },
+
+            Self::Level { scale: level_scale, child, .. } =>
+                child.size_at(scale * level_scale),
+        }
+    }
+}
+
+// IMPL: Placing
+//
+impl Node {
+    fn place(&self, x: f32, y: f32, scale: f32, layout: &mut Layout) {
+        let (width, height) = self.size_at(scale);
+
+        match self {
+            Self::Space { .. } => (),
+
+            Self::Line(_) =>
+                layout.shapes.push(Shape::Line(Rect { x, y, width, height })),
+
+            Self::Column(children) => {
+                let mut y = y;
+
+                for child in children {
+                    let (cw, ch) = child.size_at(scale);
+
+                    child.place(x + (width - cw) / 2., y, scale, layout);
+
+                    y += ch;
+                }
+            },
+
+            Self::Row { children, spacing, align } => {
+                let mut x = x;
+
+                for child in children {
+                    let (cw, ch) = child.size_at(scale);
+
+                    let offset =
+                    match align {
+                        Align::Start => 0.,
+                        Align::Center => (height - ch) / 2.,
+                    };
+
+                    child.place(x, y + offset, scale, layout);
+
+                    x += cw + *spacing as f32 * scale;
+                }
+            },
+
+            Self::Container { child, padding, .. } => {
+                let (cw, _) = child.size_at(scale);
+                let padding = *padding as f32 * scale;
+
+                child.place(x + padding + (width - 2. * padding - cw) / 2., y + padding, scale, layout);
+            },
+
+            Self::Cell { index, label, width: data_width, content } => {
+                let mut content_height = 0.;
+
+                let bounds = Rect { x, y, width, height };
+
+                // NOTE: Pushed before the contents, so that it is drawn below them.
+                //
+                let at = layout.shapes.len();
+
+                if let Some(content) = content {
+                    let (cw, ch) = content.size_at(scale);
+
+                    content.place(x + (width - cw) / 2., y, scale, layout);
+
+                    content_height = ch;
+                }
+
+                let data_width = *data_width as f32 * scale;
+
+                let data = Rect {
+                    x: x + (width - data_width) / 2.,
+                    y: y + content_height,
+
+                    width: data_width,
+                    height: DATA_HEIGHT as f32 * scale,
+                };
+
+                layout.shapes.insert(at, Shape::Cell { index: index.clone(), label: label.clone(), bounds, data });
+
+                let parent =
+                match index {
+                    ViewIndex::Leveled { level, path } if path.len() > 1 =>
+                        Some(ViewIndex::Leveled { level: *level, path: index.tail() }),
+
+                    _ => None,
+                };
+
+                layout.frames.insert(index.clone(), Frame {
+                    level: index.level(),
+
+                    bounds,
+                    data,
+
+                    parent,
+                    children: vec![],
+                });
+            },
+
+            Self::Level { level, scale: level_scale, child } => {
+                child.place(x, y, scale * level_scale, layout);
+
+                layout.columns.insert(*level, Rect { x, y, width, height });
+            },
+        }
+    }
+}
+
+// IMPL: Accessing
+//
+impl Layout {
+    pub fn frame(&self, cell: &ViewIndex) -> Option<&Frame> {
+        self.frames.get(cell)
+    }
+
+    /// Area taken by the level, unless it's hidden.
+    ///
+    pub fn column(&self, level: usize) -> Option<Rect> {
+        self.columns.get(&level).copied()
+    }
+
+    /// The innermost cell at the point.
+    ///
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<&ViewIndex> {
+        self.shapes
+            .iter()
+            .rev()
+            .find_map(|shape|
+                match shape {
+                    Shape::Cell { index, bounds, .. } if bounds.contains(x, y) =>
+                        Some(index),
+
+                    _ => None,
+                }
+            )
+    }
+
+    /// The smallest area containing all of the cells.
+    ///
+    pub fn bounds(&self, cells: &[ViewIndex]) -> Option<Rect> {
+        cells
+            .iter()
+            .filter_map(|cell| self.frame(cell))
+            .map(|frame| frame.bounds)
+            .fold(None, |acc: Option<Rect>, rect| Some(acc.map_or(rect, |acc| acc.union(&rect))))
+    }
+}
+
+impl Rect {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2., self.y + self.height / 2.)
+    }
+
+    /// The rectangle as drawn at `factor` times the natural scale.
+    ///
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+
+            width: self.width * factor,
+            height: self.height * factor,
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        self.x <= x && x <= self.x + self.width && self.y <= y && y <= self.y + self.height
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let x = f32::min(self.x, other.x);
+        let y = f32::min(self.y, other.y);
+
+        Self {
+            x,
+            y,
+
+            width: f32::max(self.x + self.width, other.x + other.width) - x,
+            height: f32::max(self.y + self.height, other.y + other.height) - y,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::testing::*;
+
+    const PASSED: &str = "\
+        extrude 0 as a wrap α
+        extrude a as b wrap β
+        extrude α β as h wrap θ
+        pass h x";
+
+
+    /// The shapes of the layout, lines drawn as `|`.
+    ///
+    fn sketch(layout: &Layout) -> Vec<(&str, [f32; 4])> {
+        layout.shapes
+            .iter()
+            .map(|shape|
+                match shape {
+                    Shape::Line(Rect { x, y, width, height }) =>
+                        ("|", [*x, *y, *width, *height]),
+
+                    Shape::Cell { label, bounds: Rect { x, y, width, height }, .. } =>
+                        (label.as_str(), [*x, *y, *width, *height]),
+                }
+            )
+            .collect()
+    }
+
+
+    #[test]
+    fn extrusion_is_laid_out_as_drawn() {
+        let layout = opetope("extrude 0 as a wrap α").layout();
+
+        assert_eq![(layout.width, layout.height), (120., 92.)];
+        assert_eq![
+            sketch(&layout),
+            vec![
+                ("a", [0., 0., 45., 92.]),
+                ("0", [8., 8., 29., 38.]),
+                ("|", [104., 0., 2., 0.]),
+                ("|", [104., 0., 2., 22.]),
+                ("α", [90., 22., 30., 38.]),
+                ("|", [104., 60., 2., 22.]),
+            ],
+        ];
+
+        assert_eq![layout.column(0), Some(Rect { x: 0., y: 0., width: 45., height: 92. })];
+        assert_eq![layout.column(1), Some(Rect { x: 90., y: 0., width: 30., height: 82. })];
+        assert_eq![layout.column(2), None];
+    }
+
+    #[test]
+    fn frames_follow_the_groups() {
+        let diagram = opetope(PASSED);
+        let layout = diagram.layout();
+
+        let [h, alpha, beta, x] = ["h", "α", "β", "x"].map(|name| index(&diagram, name));
+
+        let group = layout.frame(&h).unwrap();
+
+        assert_eq![group.level, 1];
+        assert_eq![group.parent, None];
+        assert_eq![group.children, vec![alpha.clone(), beta.clone()]];
+
+        for cell in [&alpha, &beta] {
+            let frame = layout.frame(cell).unwrap();
+
+            assert_eq![frame.parent, Some(h.clone())];
+            assert_eq![frame.bounds.union(&group.bounds), group.bounds];
+        }
+
+        assert_eq![layout.frame(&x).unwrap().level, 2];
+        assert_eq![layout.frame(&index(&diagram, "0")).unwrap().parent, None];
+    }
+
+    #[test]
+    fn columns_follow_the_levels() {
+        let diagram = opetope(PASSED);
+        let layout = diagram.layout();
+
+        let columns: Vec<_> = (0..=2).map(|level| layout.column(level).unwrap()).collect();
+
+        for (left, right) in columns.iter().zip(&columns[1..]) {
+            assert![left.x + left.width < right.x];
+        }
+
+        for index in all_indices(&diagram) {
+            let frame = layout.frame(&index).unwrap();
+
+            assert_eq![columns[frame.level].union(&frame.bounds), columns[frame.level]];
+        }
+
+        assert_eq![layout.column(3), None];
+    }
+
+    #[test]
+    fn innermost_cell_is_found() {
+        let diagram = opetope(PASSED);
+        let layout = diagram.layout();
+
+        for name in ["0", "a", "b", "h", "α", "β", "x"] {
+            let cell = index(&diagram, name);
+            let (x, y) = layout.frame(&cell).unwrap().data.center();
+
+            assert_eq![layout.cell_at(x, y), Some(&cell), "{}", name];
+        }
+
+        assert_eq![layout.cell_at(100., 10.), None];
+    }
+
+    #[test]
+    fn bounds_cover_the_cells() {
+        let diagram = opetope(PASSED);
+        let layout = diagram.layout();
+
+        let cells = [index(&diagram, "α"), index(&diagram, "β")];
+
+        assert_eq![layout.bounds(&cells), Some(Rect { x: 130., y: 44., width: 30., height: 98. })];
+        assert_eq![layout.bounds(&cells[..1]), Some(layout.frame(&cells[0]).unwrap().bounds)];
+        assert_eq![layout.bounds(&[]), None];
+    }
+
+    #[test]
+    fn folded_levels_are_shrunk_or_left_out() {
+        let diagram = opetope(PASSED);
+        let shown = diagram.layout();
+
+        let mut levels = Levels::default();
+        levels.toggle(1);
+        levels.toggle(2);
+        levels.toggle(2);
+
+        let folded = diagram.layout_folded(&levels);
+
+        let thumbnail = folded.column(1).unwrap();
+
+        assert![(thumbnail.width - shown.column(1).unwrap().width * zoom::THUMBNAIL).abs() < 0.01];
+        assert![(thumbnail.height - shown.column(1).unwrap().height * zoom::THUMBNAIL).abs() < 0.01];
+
+        assert_eq![folded.column(0), shown.column(0)];
+        assert_eq![folded.frame(&index(&diagram, "b")), shown.frame(&index(&diagram, "b"))];
+
+        assert_eq![folded.column(2), None];
+        assert_eq![folded.frame(&index(&diagram, "x")), None];
+        assert![folded.width < shown.width];
+    }
+}
@@ -1,6 +1,7 @@
-use tracing_vec::*;
+use std::fmt;
+use std::collections::HashMap;
 
-use crate::behavior::SimpleView;
+use tracing_vec::*;
 
 pub mod data;
 
@@ -13,6 +14,19 @@ pub mod utils;
 pub mod diagram;
 pub use diagram::{ Diagram, Face, MetaCell };
 
+pub mod tree;
+pub use tree::{ TracingTree, Handle };
+
+pub mod format;
+pub mod validation;
+
+pub mod layout;
+pub mod script;
+pub mod journal;
+
+#[cfg(test)]
+pub(crate) mod testing;
+
 
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -22,6 +36,7 @@ pub enum Error {
     TooMuchDepth(usize),
 
     NoSuchCell(ViewIndex),
+    NoSuchId(CellId),
     CannotSproutGroup(ViewIndex),
     NoCellWithInputs(Vec<ViewIndex>),
 
@@ -30,6 +45,16 @@ pub enum Error {
 
     CannotGroupDisconnected(Vec<ViewIndex>),
     CellsDoNotFormTree(Vec<ViewIndex>),
+
+    NotAGroup(ViewIndex),
+
+    CannotDeleteOnlyCell(ViewIndex),
+    CannotDeleteFromGroup(ViewIndex),
+    NotAnEnd(ViewIndex),
+    CellIsReferenced(ViewIndex, Vec<ViewIndex>),
+
+    CannotRestrictToNothing,
+    CannotRestrictDisconnected(Vec<ViewIndex>),
 }
 
 #[must_use = "this `EditResult` might be an `Err` variant which should be handled"]
@@ -50,15 +75,16 @@ pub enum Tail<Data> {
 
 
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum Action {
     Extrude { group: ViewIndex, contents: Vec<ViewIndex> },
     Split { group: ViewIndex, contents: Vec<ViewIndex> },
     Sprout { group: ViewIndex, end: ViewIndex },
     Delete { cell: ViewIndex },
+    Ungroup { group: ViewIndex, contents: Vec<ViewIndex> },
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum Interaction {
     InPrevious { action: Action, wraps: Vec<ViewIndex> },
 
@@ -75,13 +101,13 @@ pub enum Cell<Data> {
 #[derive(Debug, Clone)]
 pub struct IterGroups<'op, Data> {
     level: usize,
-    cells: Vec<(Vec<TimelessIndex>, &'op diagram::Cell<Data>)>,
+    cells: Vec<(Vec<TimelessIndex>, tree::NodeRef<'op, MetaCell<Data>>)>,
 }
 
 #[derive(Debug)]
 pub struct IterMutGroups<'op, Data> {
     level: usize,
-    cells: Vec<(Vec<TimelessIndex>, &'op mut diagram::Cell<Data>)>,
+    cells: Vec<(Vec<TimelessIndex>, tree::NodeMut<'op, MetaCell<Data>>)>,
 }
 
 
@@ -105,12 +131,19 @@ macro_rules! common_methods {
 //
 impl<Data> Tail<Data> {
     common_methods! {
-        level() -> usize,
+        pub level() -> usize,
+        pub indices() -> Vec<ViewIndex>,
+        pub contains(cell: &ViewIndex) -> bool,
 
-        is_before(before: &ViewIndex, after: &ViewIndex) -> bool,
-        is_at_bottom(cell: &viewing::Selection) -> Result<bool, Error>,
+        pub id_of(cell: &ViewIndex) -> Result<CellId, Error>,
+        pub index_of(id: CellId) -> Result<ViewIndex, Error>,
+        pub ids() -> Vec<(CellId, ViewIndex)>,
 
-        [mut] rename(cell: &ViewIndex, new_name: Data) -> Result<(), Error>
+        pub is_before(before: &ViewIndex, after: &ViewIndex) -> bool,
+        pub is_at_bottom(cell: &viewing::Selection) -> Result<bool, Error>,
+        pub neighbour(cell: &ViewIndex, direction: viewing::Direction) -> Option<ViewIndex>,
+
+        pub [mut] rename(cell: &ViewIndex, new_name: Data) -> Result<(), Error>
     }
 }
 
@@ -118,21 +151,36 @@ impl<Data> Tail<Data> {
 //
 impl<Data: Clone> Tail<Data> {
     common_methods! {
-        cell(cell: &ViewIndex) -> Result<Cell<Data>, Error>
+        pub cell(cell: &ViewIndex) -> Result<Cell<Data>, Error>,
+        pub cell_by_id(id: CellId) -> Result<Cell<Data>, Error>
     }
 }
 
 impl<Data: Clone> Tail<Data> {
     common_methods! {
-        deep_copy(level: usize) -> Result<Tail<Data>, Error>
+        pub deep_copy(level: usize) -> Result<Tail<Data>, Error>
     }
 }
 
-// IMPL: Viewing
+// IMPL: Layout
 //
-impl<Data: SimpleView> Tail<data::Selectable<Data>> {
+impl<Data: format::Named> Tail<Data> {
     common_methods! {
-        [mut] view(render: crate::model::Render) -> iced::Element<viewing::Message>
+        pub layout_node(levels: &crate::model::Levels) -> layout::Node
+    }
+}
+
+// IMPL: Viewing
+//
+#[cfg(feature = "gui")]
+impl<Data> Tail<Data> {
+    // NOTE: Written out, since `common_methods` cannot thread the lifetime.
+    //
+    pub(in crate::opetope) fn cells_by_index<'t>(&'t mut self, cells: &mut HashMap<ViewIndex, &'t mut Data>) {
+        match self {
+            Self::Tower(tower) => tower.cells_by_index(cells),
+            Self::Diagram(diagram) => diagram.cells_by_index(cells),
+        }
     }
 }
 
@@ -140,9 +188,11 @@ impl<Data: SimpleView> Tail<data::Selectable<Data>> {
 //
 impl<Data: Clone> Tail<Data> {
     common_methods! {
-        [mut] extrude(cell: &viewing::Selection, group: Data, wrap: Data) -> EditResult<Interaction, Data>,
-        [mut] split(cell: &viewing::Selection, group: Data, wrap_top: Data, wrap_bot: Data) -> EditResult<Interaction, Data>,
-        [mut] sprout(cell: &viewing::ViewIndex, end: Data, wrap: Data) -> EditResult<Interaction, Data>
+        pub [mut] extrude(cell: &viewing::Selection, group: Data, wrap: Data) -> EditResult<Interaction, Data>,
+        pub [mut] split(cell: &viewing::Selection, group: Data, wrap_top: Data, wrap_bot: Data) -> EditResult<Interaction, Data>,
+        pub [mut] sprout(cell: &viewing::ViewIndex, end: Data, wrap: Data) -> EditResult<Interaction, Data>,
+        pub [mut] delete(cell: &viewing::ViewIndex) -> EditResult<Interaction, Data>,
+        pub [mut] ungroup(cell: &viewing::ViewIndex) -> EditResult<Interaction, Data>
     }
 }
 
@@ -150,11 +200,22 @@ impl<Data: Clone> Tail<Data> {
 //
 impl<Data> Tail<data::Selectable<Data>> {
     common_methods! {
-        [mut] select(cell: &ViewIndex) -> Result<Option<viewing::Selection>, Error>,
-        [mut] select_unchecked(cell: &ViewIndex) -> Result<(), Error>,
-        [mut] unselect_all(max_depth: usize),
+        pub [mut] select(cell: &ViewIndex) -> Result<Option<viewing::Selection>, Error>,
+        pub [mut] select_range(cell: &ViewIndex) -> Result<Option<viewing::Selection>, Error>,
+        pub [mut] select_all() -> Option<viewing::Selection>,
+        pub [mut] select_unchecked(cell: &ViewIndex) -> Result<(), Error>,
+        pub [mut] unselect_all(max_depth: usize),
 
-        selected_cells() -> Option<Selection>
+        pub selected_cells() -> Option<Selection>,
+
+        pub [mut] focus(cell: &ViewIndex) -> Result<(), Error>,
+        pub [mut] unfocus_all(),
+
+        pub focused_cell() -> Option<ViewIndex>,
+
+        pub [mut] highlight(cell: &ViewIndex, highlight: data::Highlight) -> Result<(), Error>,
+        pub [mut] hover(cell: &ViewIndex) -> Result<(), Error>,
+        pub [mut] unhighlight_all()
     }
 }
 
@@ -170,10 +231,24 @@ impl<Data> Tail<Data> {
     }
 }
 
-impl<Data> Tail<data::Selectable<Data>> {
-    pub fn retain_selected(self) -> Result<Option<Self>, Error> {
+impl<Data: Clone> Tail<Data> {
+    pub(in super) fn restrict(&self, keep: &[ViewIndex]) -> Result<(Self, HashMap<ViewIndex, ViewIndex>), Error> {
         match self {
-            Self::Tower(t) => Ok(t.retain_selected().map(Self::Tower)),
+            Self::Tower(t) =>
+                t.restrict(keep)
+                    .map(|(t, mapping)| (Self::Tower(t), mapping)),
+
+            Self::Diagram(d) =>
+                d.restrict(keep)
+                    .map(|(d, mapping)| (Self::Diagram(Box::new(d)), mapping)),
+        }
+    }
+}
+
+impl<Data: Clone> Tail<data::Selectable<Data>> {
+    pub fn retain_selected(&self) -> Result<Option<Self>, Error> {
+        match self {
+            Self::Tower(t) => Ok(t.retain_selected()?.map(Self::Tower)),
             Self::Diagram(d) => d.retain_selected(),
         }
     }
@@ -250,12 +325,12 @@ impl<'op, Data: 'op> Iterator for IterGroups<'op, Data> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some((path, cell)) = self.cells.pop() {
-            if let Some(content) = &cell.content {
+            if let Some(content) = cell.children() {
                 let mut ends = vec![];
                 let level = self.level;
 
                 let inner_cells = content
-                    .iter_timeless_indices()
+                    .into_iter()
                     .map(|(index, cell)| (
                         {
                             let mut path = path.clone();
@@ -289,7 +364,7 @@ impl<'op, Data: 'op> Iterator for IterGroups<'op, Data> {
                 return
                     Some((
                         face,
-                        &cell.meta,
+                        cell.val(),
                     ))
             }
         }
@@ -303,12 +378,12 @@ impl<'op, Data: 'op> Iterator for IterMutGroups<'op, Data> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some((path, cell)) = self.cells.pop() {
-            if let Some(content) = &mut cell.content {
+            if let Some(content) = cell.children {
                 let mut ends = vec![];
                 let level = self.level;
 
                 let inner_cells = content
-                    .iter_mut_timeless_indices()
+                    .into_iter()
                     .map(|(index, cell)| (
                         {
                             let mut path = path.clone();
@@ -342,7 +417,7 @@ impl<'op, Data: 'op> Iterator for IterMutGroups<'op, Data> {
                 return
                     Some((
                         face,
-                        &mut cell.meta,
+                        cell.val,
                     ))
             }
         }
@@ -378,8 +453,79 @@ impl<Data> Cell<Data> {
 }
 
 
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CannotSproutGroup(_cell) =>
+                write![fmt, "Cannot sprout group"],
+
+            Self::NoSuchId(id) =>
+                write![fmt, "There is no cell {}", id],
+
+            Self::CannotGroupDisconnected(_sel) =>
+                write![fmt, "Cells are not connected"],
+
+            Self::CellsDoNotFormTree(_sel) =>
+                write![fmt, "Cells do not form a tree"],
+
+            Self::NotAGroup(cell) =>
+                write![fmt, "Cannot ungroup {}, it is not a group", cell],
+
+            Self::CannotDeleteOnlyCell(cell) =>
+                write![fmt, "Cannot delete {}, it would leave a level without cells", cell],
+
+            Self::CannotDeleteFromGroup(cell) =>
+                write![fmt, "Cannot delete {}, it would change the face of its group", cell],
+
+            Self::NotAnEnd(cell) =>
+                write![fmt, "Cannot delete {}, it connects other cells of a level", cell],
 
-pub use viewing::{ Message, ViewIndex, Selection };
+            Self::CellIsReferenced(cell, by) =>
+                write![
+                    fmt,
+                    "Cannot delete {}, it is used by {}",
+                    cell,
+                    by.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+                ],
+
+            Self::CannotRestrictToNothing =>
+                write![fmt, "No cells to keep"],
+
+            Self::CannotRestrictDisconnected(cells) =>
+                write![
+                    fmt,
+                    "Cannot keep only {}, they are not contiguous",
+                    cells.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+                ],
+
+
+            // Internal
+            //
+            Self::IndexError(e) =>
+                write![fmt, "INTERNAL: error while indexing: {:?}", e],
+
+            Self::TooMuchDepth(depth) =>
+                write![fmt, "INTERNAL: too much depth: {}", depth],
+
+            Self::NoSuchCell(cell) =>
+                write![fmt, "INTERNAL: cell does not exist: {}", cell],
+
+            Self::NoCellWithInputs(inputs) =>
+                write![fmt, "INTERNAL: no cell with inputs: {:?}", inputs],
+
+            Self::CannotSplitBoundaryCells(sel) =>
+                write![fmt, "INTERNAL: cannot split boundary cells: {:?}", sel],
+
+            Self::CannotExtrudeNestedCells(sel) =>
+                write![fmt, "INTERNAL: cannot extrude nested cells: {:?}", sel],
+
+        }
+    }
+}
+
+
+
+pub use viewing::{ Message, ViewIndex, CellId, Selection, Direction };
 pub mod viewing {
     use super::*;
     use std::fmt;
@@ -390,6 +536,17 @@ pub mod viewing {
         Leveled { level: usize, path: Vec<TimelessIndex> },
     }
 
+    /// Identifier given to a cell when it's created. Unlike its `ViewIndex`, it doesn't change when
+    /// the cell is grouped, ungrouped or moved by other edits, and it's saved in files.
+    ///
+    /// The level is counted from the tower, which is level 0.
+    ///
+    #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
+    pub struct CellId {
+        pub level: usize,
+        pub number: u64,
+    }
+
     #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
     pub enum Selection {
         Ground(TimelessIndex),
@@ -400,6 +557,35 @@ pub mod viewing {
     pub enum Message {
         Idle,
         Select(ViewIndex),
+
+        Hover(ViewIndex),
+        Unhover(ViewIndex),
+    }
+
+    /// A step of the keyboard focus from one cell to another.
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Direction {
+        /// To a cell of the next level, which has the focused cell as its output or input.
+        ///
+        Up,
+        /// To the output of the focused cell.
+        ///
+        Down,
+
+        /// To the previous cell of the same cell space.
+        ///
+        Left,
+        /// To the next cell of the same cell space.
+        ///
+        Right,
+
+        /// To the first cell inside the focused group.
+        ///
+        In,
+        /// To the group containing the focused cell.
+        ///
+        Out,
     }
 
     pub(in super) trait Index {
@@ -442,6 +628,41 @@ pub mod viewing {
         }
     }
 
+    // IMPL: Initialization
+    //
+    impl Selection {
+        /// Selects sibling cells, listed in their order within the cell space.
+        ///
+        pub fn from_cells(cells: &[ViewIndex]) -> Result<Self, Error> {
+            let disconnected = || Error::CannotGroupDisconnected(cells.to_vec());
+
+            match cells {
+                [ViewIndex::Ground(index)] =>
+                    Ok(Self::Ground(*index)),
+
+                [ViewIndex::Leveled { level, path: first }, ..] => {
+                    let (_, path) = first.split_last().ok_or_else(disconnected)?;
+
+                    let mut heads = vec![];
+
+                    for cell in cells {
+                        match cell {
+                            ViewIndex::Leveled { level: l, path: p } if l == level && !p.is_empty() && &p[.. p.len() - 1] == path =>
+                                heads.push(*p.last().unwrap()),
+
+                            _ =>
+                                return Err(disconnected()),
+                        }
+                    }
+
+                    Ok(Self::Leveled { level: *level, path: path.to_vec(), cells: heads })
+                },
+
+                _ => Err(disconnected()),
+            }
+        }
+    }
+
     // IMPL: Accessing
     //
     impl Selection {
@@ -544,4 +765,10 @@ pub mod viewing {
             }
         }
     }
+
+    impl fmt::Display for CellId {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            write![fmt, "@{}:{}", self.level, self.number]
+        }
+    }
 }
@@ -0,0 +1,653 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{ de, Serialize, Serializer, Deserialize, Deserializer };
+use tracing_vec::TimelessIndex;
+
+use super::{ Diagram, MetaCell, Tail, ViewIndex, Selection, Interaction, format::Named, journal::Entry };
+
+
+
+/// A sequence of edits, one command per line:
+///
+/// ```text
+/// # Comments start with `#`, names containing spaces or keywords are quoted.
+/// extrude <cell>... as <group> wrap <wrap>
+/// split <cell>... as <group> wrap <top wrap> <bottom wrap>
+/// sprout <cell> as <end> wrap <wrap>
+/// pass (<group> <wrap>)...
+/// rename <cell> <name>
+/// delete <cell>
+/// ungroup <group>
+/// ```
+///
+/// Cells are addressed either by their index as displayed (`2↑0.1`, or `2^0.1`, and `↓0` in
+/// the tower), by their identifier (`@2:5`, see `CellId`), or by their name, if it is unique.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    line: usize,
+    source: String,
+
+    command: Command,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Extrude { cells: Vec<String>, group: String, wrap: String },
+    Split { cells: Vec<String>, group: String, wrap_top: String, wrap_bot: String },
+    Sprout { cell: String, end: String, wrap: String },
+    Pass { wraps: Vec<(String, String)> },
+    Rename { cell: String, name: String },
+    Delete { cell: String },
+    Ungroup { group: String },
+}
+
+
+/// The first command which could not be parsed or applied.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub command: String,
+
+    pub reason: Reason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    Syntax(String),
+
+    NoSuchCell(String),
+    AmbiguousCell(String, Vec<ViewIndex>),
+
+    NotAGroup(ViewIndex),
+    MissingWrap(ViewIndex),
+
+    Opetope(super::Error),
+}
+impl From<super::Error> for Reason {
+    fn from(e: super::Error) -> Self {
+        Self::Opetope(e)
+    }
+}
+
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+
+
+// IMPL: Parsing
+//
+impl Script {
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let mut steps = vec![];
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+
+            let fail = |reason| Error { line, command: text.trim().to_string(), reason };
+
+            let tokens = tokenize(text).map_err(|e| fail(Reason::Syntax(e)))?;
+
+            if let Some(command) = Command::parse(&tokens).map_err(|e| fail(Reason::Syntax(e)))? {
+                steps.push(Step {
+                    line,
+                    source: text.trim().to_string(),
+
+                    command,
+                });
+            }
+        }
+
+        Ok(Self { steps })
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.steps.iter().map(|step| &step.command)
+    }
+}
+
+impl Command {
+    fn parse(tokens: &[Token]) -> Result<Option<Self>, String> {
+        let (head, args) =
+        if let Some((head, args)) = tokens.split_first() {
+            (head, args)
+
+        } else {
+            return Ok(None);
+        };
+
+        let texts = |tokens: &[Token]| tokens.iter().map(|token| token.text.clone()).collect::<Vec<_>>();
+
+        let command =
+        match head.text.as_str() {
+            "extrude" | "split" => {
+                let pos =
+                args.iter()
+                    .position(|token| token.is("as"))
+                    .ok_or_else(|| format!["expected `{} <cell>... as <group> wrap ...`", head.text])?;
+
+                let (cells, rest) = args.split_at(pos);
+
+                if cells.is_empty() {
+                    return Err("no cells given".to_string());
+                }
+
+                let cells = texts(cells);
+
+                match (head.text.as_str(), rest) {
+                    ("extrude", [_, group, wrap_kw, wrap]) if wrap_kw.is("wrap") =>
+                        Self::Extrude { cells, group: group.text.clone(), wrap: wrap.text.clone() },
+
+                    ("split", [_, group, wrap_kw, wrap_top, wrap_bot]) if wrap_kw.is("wrap") =>
+                        Self::Split { cells, group: group.text.clone(), wrap_top: wrap_top.text.clone(), wrap_bot: wrap_bot.text.clone() },
+
+                    ("extrude", _) =>
+                        return Err("expected `extrude <cell>... as <group> wrap <wrap>`".to_string()),
+
+                    _ =>
+                        return Err("expected `split <cell>... as <group> wrap <top wrap> <bottom wrap>`".to_string()),
+                }
+            },
+
+            "sprout" =>
+                match args {
+                    [cell, as_kw, end, wrap_kw, wrap] if as_kw.is("as") && wrap_kw.is("wrap") =>
+                        Self::Sprout { cell: cell.text.clone(), end: end.text.clone(), wrap: wrap.text.clone() },
+
+                    _ =>
+                        return Err("expected `sprout <cell> as <end> wrap <wrap>`".to_string()),
+                },
+
+            "pass" =>
+                if args.len() % 2 == 0 {
+                    Self::Pass {
+                        wraps: args
+                            .chunks(2)
+                            .map(|pair| (pair[0].text.clone(), pair[1].text.clone()))
+                            .collect(),
+                    }
+
+                } else {
+                    return Err("expected `pass (<group> <wrap>)...`".to_string());
+                },
+
+            "rename" =>
+                match args {
+                    [cell, name] =>
+                        Self::Rename { cell: cell.text.clone(), name: name.text.clone() },
+
+                    _ =>
+                        return Err("expected `rename <cell> <name>`".to_string()),
+                },
+
+            "delete" =>
+                match args {
+                    [cell] =>
+                        Self::Delete { cell: cell.text.clone() },
+
+                    _ =>
+                        return Err("expected `delete <cell>`".to_string()),
+                },
+
+            "ungroup" =>
+                match args {
+                    [group] =>
+                        Self::Ungroup { group: group.text.clone() },
+
+                    _ =>
+                        return Err("expected `ungroup <group>`".to_string()),
+                },
+
+            _ =>
+                return Err(format!["unknown command `{}`", head.text]),
+        };
+
+        Ok(Some(command))
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        Self::parse(&tokenize(line)?)?
+            .ok_or_else(|| "empty command".to_string())
+    }
+}
+
+impl Token {
+    fn is(&self, keyword: &str) -> bool {
+        !self.quoted && self.text == keyword
+    }
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c == '#' {
+            break;
+
+        } else if c.is_whitespace() {
+            chars.next();
+
+        } else if c == '"' {
+            chars.next();
+
+            let mut text = String::new();
+
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.extend(chars.next()),
+                    Some(c) => text.push(c),
+
+                    None => return Err("unterminated quote".to_string()),
+                }
+            }
+
+            tokens.push(Token { text, quoted: true });
+
+        } else {
+            let mut text = String::new();
+
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '#' || c == '"' {
+                    break;
+                }
+
+                text.push(c);
+                chars.next();
+            }
+
+            tokens.push(Token { text, quoted: false });
+        }
+    }
+
+    Ok(tokens)
+}
+
+
+// IMPL: Running
+//
+impl Script {
+    /// Applies the commands in order, returning them as journal entries. If any of them fails, the
+    /// diagram is left untouched.
+    ///
+    pub fn run<Data: Clone + Named>(&self, diagram: &mut Diagram<Data>) -> Result<Vec<Entry>, Error> {
+        let mut edited = diagram.clone();
+        let mut entries = vec![];
+
+        for step in &self.steps {
+            let interactions =
+            step.command
+                .apply(&mut edited)
+                .map_err(|reason| Error { line: step.line, command: step.source.clone(), reason })?;
+
+            entries.push(Entry::new(step.command.clone(), interactions));
+        }
+
+        *diagram = edited;
+
+        Ok(entries)
+    }
+}
+
+impl Command {
+    /// Applies the command, returning the interactions reported by the edit.
+    ///
+    pub fn apply<Data: Clone + Named>(&self, diagram: &mut Diagram<Data>) -> Result<Vec<Interaction>, Reason> {
+        let interaction =
+        match self {
+            Self::Extrude { cells, group, wrap } => {
+                let cells = select(diagram, cells)?;
+
+                diagram
+                    .extrude(&cells, Data::from_name(group.clone()), Data::from_name(wrap.clone()))
+                    .ok()?
+            },
+
+            Self::Split { cells, group, wrap_top, wrap_bot } => {
+                let cells = select(diagram, cells)?;
+
+                diagram
+                    .split(&cells, Data::from_name(group.clone()), Data::from_name(wrap_top.clone()), Data::from_name(wrap_bot.clone()))
+                    .ok()?
+            },
+
+            Self::Sprout { cell, end, wrap } => {
+                let cell = resolve(diagram, cell)?;
+
+                diagram
+                    .sprout(&cell, Data::from_name(end.clone()), Data::from_name(wrap.clone()))
+                    .ok()?
+            },
+
+            Self::Pass { wraps } => {
+                let given =
+                wraps
+                    .iter()
+                    .map(|(group, wrap)| Ok((resolve(diagram, group)?, wrap)))
+                    .collect::<Result<Vec<_>, Reason>>()?;
+
+                let groups: Vec<_> =
+                diagram
+                    .iter_groups()
+                    .map(|(face, _)| face)
+                    .collect();
+
+                if let Some((group, _)) = given.iter().find(|(group, _)| groups.iter().all(|face| &face.fill != group)) {
+                    return Err(Reason::NotAGroup(group.clone()));
+                }
+
+                let mut metas = vec![];
+
+                // NOTE: In the order in which the app asks for the wraps.
+                //
+                for face in groups.into_iter().rev() {
+                    let (_, wrap) =
+                    given
+                        .iter()
+                        .find(|(group, _)| group == &face.fill)
+                        .ok_or_else(|| Reason::MissingWrap(face.fill.clone()))?;
+
+                    metas.push(MetaCell { data: Data::from_name(wrap.to_string()), face });
+                }
+
+                diagram.into_next(metas)?;
+
+                return Ok(vec![]);
+            },
+
+            Self::Rename { cell, name } => {
+                let cell = resolve(diagram, cell)?;
+
+                diagram.rename(&cell, Data::from_name(name.clone()))?;
+
+                return Ok(vec![]);
+            },
+
+            Self::Delete { cell } => {
+                let cell = resolve(diagram, cell)?;
+
+                diagram
+                    .delete(&cell)
+                    .ok()?
+            },
+
+            Self::Ungroup { group } => {
+                let group = resolve(diagram, group)?;
+
+                diagram
+                    .ungroup(&group)
+                    .ok()?
+            },
+        };
+
+        Ok(vec![interaction])
+    }
+}
+
+/// All cells of the diagram and the levels below, each group preceding its contents.
+///
+fn all_indices<Data>(diagram: &Diagram<Data>) -> Vec<ViewIndex> {
+    let mut indices = diagram.indices();
+    let mut tail = diagram.prev();
+
+    loop {
+        let mut below = tail.indices();
+        below.append(&mut indices);
+
+        indices = below;
+
+        match tail {
+            Tail::Diagram(d) => tail = d.prev(),
+            Tail::Tower(_) => break indices,
+        }
+    }
+}
+
+fn resolve<Data: Clone + Named>(diagram: &Diagram<Data>, cell: &str) -> Result<ViewIndex, Reason> {
+    let indices = all_indices(diagram);
+
+    if let Some(index) = parse_index(cell).filter(|index| indices.contains(index)) {
+        return Ok(index);
+    }
+
+    if let Some(index) = indices.iter().find(|index| matches![diagram.id_of(index), Ok(id) if id.to_string() == cell]) {
+        return Ok(index.clone());
+    }
+
+    let mut named = vec![];
+
+    for index in indices {
+        if diagram.cell(&index)?.data().name() == cell {
+            named.push(index);
+        }
+    }
+
+    match &named[..] {
+        [index] => Ok(index.clone()),
+        [] => Err(Reason::NoSuchCell(cell.to_string())),
+
+        _ => Err(Reason::AmbiguousCell(cell.to_string(), named)),
+    }
+}
+
+/// Reads an index as displayed, with the segments of the path written as plain numbers
+/// (`2↑0.1`, `2^0.1` or `↓0`).
+///
+fn parse_index(cell: &str) -> Option<ViewIndex> {
+    let segment = |segment: &str| {
+        segment
+            .strip_suffix("@!")
+            .unwrap_or(segment)
+            .parse()
+            .ok()
+            // NOTE: The index is only compared with the existing ones, never used to look up a cell.
+            //
+            .map(|pos| unsafe { TimelessIndex::from_raw_parts(pos) })
+    };
+
+    if let Some(ground) = cell.strip_prefix('↓') {
+        return segment(ground).map(ViewIndex::Ground);
+    }
+
+    let (level, path) = cell.split_once(['↑', '^'])?;
+    let level: usize = level.parse().ok()?;
+
+    Some(ViewIndex::Leveled {
+        level: level.checked_sub(1)?,
+
+        path: path
+            .split('.')
+            .map(segment)
+            .collect::<Option<_>>()?,
+    })
+}
+
+fn select<Data: Clone + Named>(diagram: &Diagram<Data>, cells: &[String]) -> Result<Selection, Reason> {
+    let cells =
+    cells
+        .iter()
+        .map(|cell| resolve(diagram, cell))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ordered: Vec<_> =
+    all_indices(diagram)
+        .into_iter()
+        .filter(|index| cells.contains(index))
+        .collect();
+
+    Ok(Selection::from_cells(&ordered)?)
+}
+
+
+impl fmt::Display for Command {
+    /// Writes the command as a line of a script.
+    ///
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let cells = |cells: &[String]| cells.iter().map(|cell| quote(cell)).collect::<Vec<_>>().join(" ");
+
+        match self {
+            Self::Extrude { cells: selected, group, wrap } =>
+                write![fmt, "extrude {} as {} wrap {}", cells(selected), quote(group), quote(wrap)],
+
+            Self::Split { cells: selected, group, wrap_top, wrap_bot } =>
+                write![fmt, "split {} as {} wrap {} {}", cells(selected), quote(group), quote(wrap_top), quote(wrap_bot)],
+
+            Self::Sprout { cell, end, wrap } =>
+                write![fmt, "sprout {} as {} wrap {}", quote(cell), quote(end), quote(wrap)],
+
+            Self::Pass { wraps } => {
+                write![fmt, "pass"]?;
+
+                for (group, wrap) in wraps {
+                    write![fmt, " {} {}", quote(group), quote(wrap)]?;
+                }
+
+                Ok(())
+            },
+
+            Self::Rename { cell, name } =>
+                write![fmt, "rename {} {}", quote(cell), quote(name)],
+
+            Self::Delete { cell } =>
+                write![fmt, "delete {}", quote(cell)],
+
+            Self::Ungroup { group } =>
+                write![fmt, "ungroup {}", quote(group)],
+        }
+    }
+}
+
+/// Quotes the token if it would not be read back as a single name.
+///
+fn quote(token: &str) -> String {
+    let plain =
+    !token.is_empty()
+        && token != "as"
+        && token != "wrap"
+        && !token.chars().any(|c| c.is_whitespace() || c == '#' || c == '"');
+
+    if plain {
+        token.to_string()
+
+    } else {
+        format!["\"{}\"", token.replace('\\', "\\\\").replace('"', "\\\"")]
+    }
+}
+
+// NOTE: Stored as script lines, which are readable in saved files.
+//
+impl Serialize for Command {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Command {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write![fmt, "line {} (`{}`): {}", self.line, self.command, self.reason]
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(e) =>
+                write![fmt, "{}", e],
+
+            Self::NoSuchCell(cell) =>
+                write![fmt, "no cell `{}`", cell],
+
+            Self::AmbiguousCell(cell, candidates) =>
+                write![
+                    fmt,
+                    "`{}` may be any of {}",
+                    cell,
+                    candidates
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ],
+
+            Self::NotAGroup(cell) =>
+                write![fmt, "{} is not a group", cell],
+
+            Self::MissingWrap(group) =>
+                write![fmt, "no wrap given for group {}", group],
+
+            Self::Opetope(e) =>
+                write![fmt, "{}", e],
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::testing::{ names, opetope };
+
+    #[test]
+    fn cells_are_resolved_by_their_displayed_index() {
+        let mut diagram = opetope("\
+            extrude 0 as a wrap α
+            extrude a as b wrap β");
+
+        Script::parse("rename ↓1 x\nrename 1↑0 y\nrename 1^1 z")
+            .unwrap()
+            .run(&mut diagram)
+            .unwrap();
+
+        assert_eq![names(&diagram, 0), ["0", "x", "b"]];
+        assert_eq![names(&diagram, 1), ["y", "z"]];
+    }
+
+    #[test]
+    fn every_index_resolves_to_itself() {
+        let diagram = opetope("\
+            extrude 0 as a wrap α
+            extrude a as b wrap β
+            extrude α β as h wrap θ");
+
+        for index in all_indices(&diagram) {
+            let displayed = index.to_string().replace("@!", "");
+
+            assert_eq![resolve(&diagram, &displayed).unwrap(), index];
+            assert_eq![resolve(&diagram, &displayed.replace('↑', "^")).unwrap(), index];
+        }
+    }
+
+    #[test]
+    fn missing_index_is_reported() {
+        let diagram = opetope("extrude 0 as a wrap α");
+
+        assert![matches![resolve(&diagram, "2↑5"), Err(Reason::NoSuchCell(_))]];
+        assert![matches![resolve(&diagram, "2↑0.0"), Err(Reason::NoSuchCell(_))]];
+    }
+}
@@ -0,0 +1,64 @@
+use super::{ data::Selectable, format::Named, script::Script, viewing::Index, Diagram, Tail, Tower, ViewIndex };
+
+
+
+pub type Opetope = Diagram<Selectable<String>>;
+
+
+/// Builds an opetope by running the script on a tower of a single cell `0`.
+///
+pub fn opetope(script: &str) -> Opetope {
+    let (_, tower) = Tower::init(Selectable::from_name("0".to_string()));
+    let mut diagram = tower.into_next().unwrap();
+
+    Script::parse(script)
+        .unwrap()
+        .run(&mut diagram)
+        .unwrap();
+
+    diagram
+}
+
+/// All cells of the opetope, from the tower up, each group preceding its contents.
+///
+pub fn all_indices<Data>(diagram: &Diagram<Data>) -> Vec<ViewIndex> {
+    let mut indices = diagram.indices();
+    let mut tail = diagram.prev();
+
+    loop {
+        let mut below = tail.indices();
+        below.append(&mut indices);
+
+        indices = below;
+
+        match tail {
+            Tail::Diagram(d) => tail = d.prev(),
+            Tail::Tower(_) => break indices,
+        }
+    }
+}
+
+/// The index of the only cell named `name`.
+///
+pub fn index<Data: Clone + Named>(diagram: &Diagram<Data>, name: &str) -> ViewIndex {
+    let named: Vec<_> =
+    all_indices(diagram)
+        .into_iter()
+        .filter(|index| diagram.cell(index).unwrap().data().name() == name)
+        .collect();
+
+    match &named[..] {
+        [index] => index.clone(),
+        _ => panic!["{} cells named `{}`", named.len(), name],
+    }
+}
+
+/// Names of the cells of the level, where the tower is level 0, each group preceding its contents.
+///
+pub fn names<Data: Clone + Named>(diagram: &Diagram<Data>, level: usize) -> Vec<String> {
+    all_indices(diagram)
+        .iter()
+        .filter(|index| index.level() == level)
+        .map(|index| diagram.cell(index).unwrap().data().name())
+        .collect()
+}
@@ -0,0 +1,714 @@
+use std::collections::HashMap;
+
+use super::{
+    *,
+    viewing::{ ViewIndex, Selection, Index }
+};
+
+use serde::{ Serialize, Deserialize };
+
+use super::{ format::Named, layout, diagram::Removal };
+use crate::styles::container::PADDING;
+use crate::model::Levels;
+
+
+macro_rules! interaction {
+    (
+        $self:ident . $name:ident ( $verify:ident [$ref_name:ident : $ref_ty:ty] $(, $arg:ident : $arg_ty:ty)* )
+        => $action:ident { $field:ident $(: $override:expr)? $(, $rest:ident $(: $r_override:expr)?)* }
+        => $method:ident ( $($call_arg:ident),* )
+        $(where if !$invariant:ident => $err:ident)?
+    ) => {
+        pub fn $name(&mut $self, $ref_name: & $ref_ty $(, $arg: $arg_ty)*) -> EditResult<Interaction, Data> {
+            if let Ok(index) = Self::$verify($ref_name) {
+                $(
+                    match $self.$invariant($ref_name) {
+                        Ok($invariant) =>
+                            if !$invariant {
+                                return EditResult::Err(Error::$err($ref_name.clone()));
+                            },
+
+                        Err(e) => return EditResult::Err(e),
+                    }
+                )?
+
+                match
+                $self
+                    .cells
+                    .$method(index $(, $call_arg)*)
+                    .map(|timed| $self.cells.into_timeless(timed).unwrap())
+                    .map(|index| $self.number(index))
+                    .map(ViewIndex::Ground)
+                    .map_err(Error::IndexError)
+                {
+                    Ok($field) =>
+                        EditResult::Ok(Interaction::Here {
+                            action: Action::$action { $($rest $(: $r_override)?, )* $field $(: $override)? },
+                        }),
+
+                    Err(e) =>
+                        EditResult::Err(e),
+                }
+
+            } else {
+                EditResult::Err(Error::TooMuchDepth($ref_name.level()))
+            }
+        }
+    };
+}
+
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tower<Data> {
+    cells: TracingVec<Data>,
+
+    // NOTE: Numbers of removed cells are kept, their timeless indices are never reused.
+    //
+    ids: HashMap<TimelessIndex, u64>,
+    next_id: u64,
+}
+
+
+
+// IMPL: Initialization
+//
+impl<Data> Tower<Data> {
+    pub fn init(root: Data) -> (ViewIndex, Self) {
+        let this = Self::from_cells(vec![(root, None)], 0);
+        let index = this.cells.timeless_indices().next().unwrap();
+
+        (ViewIndex::Ground(index), this)
+    }
+
+    // NOTE: `cells` must be non-empty, the given numbers must be distinct. Cells without a number
+    //       are numbered after all the given ones and `next_id`.
+    //
+    pub(in super) fn from_cells(cells: Vec<(Data, Option<u64>)>, next_id: u64) -> Self {
+        let (data, numbers): (Vec<_>, Vec<_>) = cells.into_iter().unzip();
+
+        let cells = TracingVec::from(data);
+
+        let mut next_id =
+        numbers
+            .iter()
+            .flatten()
+            .map(|id| id + 1)
+            .max()
+            .unwrap_or(0)
+            .max(next_id);
+
+        let mut ids = HashMap::new();
+
+        for (index, id) in cells.timeless_indices().zip(numbers) {
+            let id = id.unwrap_or_else(|| {
+                next_id += 1;
+
+                next_id - 1
+            });
+
+            ids.insert(index, id);
+        }
+
+        Self {
+            cells,
+
+            ids,
+            next_id,
+        }
+    }
+}
+
+// IMPL: Editing
+//
+impl<Data> Tower<Data> {
+    interaction! {
+        self.extrude(extract[sel: Selection], group: Data, _wrap: Data)
+            => Extrude { group, contents: self.contents_of(&group.path()).unwrap() }
+            => try_insert_after(group)
+
+        where if !is_bottom => CannotExtrudeNestedCells
+    }
+
+    interaction! {
+        self.split(extract[sel: Selection], group: Data, _wrap_top: Data, _wrap_bot: Data)
+            => Split { group, contents: self.contents_of(&group.path()).unwrap() }
+            => try_insert_after(group)
+
+        where if !is_middle => CannotSplitBoundaryCells
+    }
+
+    interaction! {
+        self.sprout(valid_level[index: ViewIndex], end: Data, _wrap: Data)
+            => Sprout { end, group: index.clone() }
+            => try_insert_before(end)
+
+        where if !is_end => CannotSproutGroup
+    }
+
+    interaction! {
+        self.delete(valid_level[index: ViewIndex])
+            => Delete { cell }
+            => try_remove()
+    }
+
+    // NOTE: Cells of the tower are not groups, even though they are drawn nested.
+    //
+    pub fn ungroup(&mut self, cell: &ViewIndex) -> EditResult<Interaction, Data> {
+        EditResult::Err(Error::NotAGroup(cell.clone()))
+    }
+}
+
+impl<Data> Tower<Data> {
+    /// Numbers the cell if it's new.
+    ///
+    fn number(&mut self, index: TimelessIndex) -> TimelessIndex {
+        if !self.ids.contains_key(&index) {
+            self.ids.insert(index, self.next_id);
+            self.next_id += 1;
+        }
+
+        index
+    }
+
+    /// Removes the cell. The cells of the first level may only wrap it, as made by extruding the
+    /// cell below or sprouting into the cell above.
+    ///
+    pub(in super) fn remove(&mut self, cell: &ViewIndex) -> Result<Removal, Error> {
+        let index = Self::valid_level(cell)?;
+
+        let order: Vec<_> = self.cells.timeless_indices().collect();
+
+        let pos =
+        order
+            .iter()
+            .position(|&other| other == index)
+            .ok_or_else(|| Error::NoSuchCell(cell.clone()))?;
+
+        if order.len() == 1 {
+            return Err(Error::CannotDeleteOnlyCell(cell.clone()));
+        }
+
+        let mut wraps = vec![];
+
+        if let Some(&inner) = pos.checked_sub(1).and_then(|pos| order.get(pos)) {
+            wraps.push(Face { ends: vec![ViewIndex::Ground(inner)], fill: cell.clone() });
+        }
+
+        if let Some(&outer) = order.get(pos + 1) {
+            wraps.push(Face { ends: vec![cell.clone()], fill: ViewIndex::Ground(outer) });
+        }
+
+        self.cells
+            .try_remove(index)
+            .map_err(Error::IndexError)?;
+
+        Ok(Removal { cells: vec![cell.clone()], wraps, moved: vec![] })
+    }
+}
+
+// IMPL: Transforming
+//
+impl<Data> Tower<Data> {
+    pub fn into_next(self) -> Result<Diagram<Data>, Error> {
+        Diagram::new(Tail::Tower(self))
+    }
+}
+
+// IMPL: Accessing
+//
+impl<Data> Tower<Data> {
+    pub const fn level(&self) -> usize {
+        0
+    }
+
+    /// Indices of all cells of the tower, from the innermost one.
+    ///
+    pub fn indices(&self) -> Vec<ViewIndex> {
+        self.cells
+            .timeless_indices()
+            .map(ViewIndex::Ground)
+            .collect()
+    }
+
+    pub(in super) fn cells(&self) -> &TracingVec<Data> {
+        &self.cells
+    }
+
+    /// The number to be given to the next cell.
+    ///
+    pub(in super) const fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub(in super) fn is_end(&self, cell: &dyn super::viewing::Index) -> Result<bool, Error> {
+        let index = Self::valid_level(cell)?;
+
+        let index =
+        self.cells
+            .into_timed(index)
+            .map_err(|_| Error::NoSuchCell(ViewIndex::Ground(index)))?;
+
+        Ok(
+            self.cells
+                .try_first_index()
+                .map(|first| self.cells.indices_eq(first, index).unwrap())
+                .unwrap_or(false)
+        )
+    }
+
+    pub(in super) fn is_bottom(&self, cells: &Selection) -> Result<bool, Error> {
+        let index = Self::valid_level(cells)?;
+
+        let index =
+        self.cells
+            .into_timed(index)
+            .map_err(|_| Error::NoSuchCell(ViewIndex::Ground(index)))?;
+
+        Ok(
+            self.cells
+                .try_last_index()
+                .map(|last| self.cells.indices_eq(last, index).unwrap())
+                .unwrap_or(false)
+        )
+    }
+
+    pub(in super) fn is_middle(&self, cell: &Selection) -> Result<bool, Error> {
+        let index = Self::valid_level(cell)?;
+
+        Ok(!self.is_bottom(&Selection::Ground(index))?)
+    }
+
+    pub fn contains(&self, cell: &ViewIndex) -> bool {
+        cell.as_ground()
+            .map(|index| self.cells.get(index).is_ok())
+            .unwrap_or(false)
+    }
+
+    pub fn id_of(&self, cell: &ViewIndex) -> Result<CellId, Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get(index)
+            .map_err(|_| Error::NoSuchCell(cell.clone()))?;
+
+        Ok(CellId { level: 0, number: self.ids[&index] })
+    }
+
+    pub fn index_of(&self, id: CellId) -> Result<ViewIndex, Error> {
+        if id.level != 0 {
+            return Err(Error::TooMuchDepth(id.level));
+        }
+
+        self.ids
+            .iter()
+            .find(|&(&index, &number)| number == id.number && self.cells.get(index).is_ok())
+            .map(|(&index, _)| ViewIndex::Ground(index))
+            .ok_or(Error::NoSuchId(id))
+    }
+
+    /// Identifiers of all cells of the tower along with their indices, from the innermost cell.
+    ///
+    pub fn ids(&self) -> Vec<(CellId, ViewIndex)> {
+        self.cells
+            .timeless_indices()
+            .map(|index| (CellId { level: 0, number: self.ids[&index] }, ViewIndex::Ground(index)))
+            .collect()
+    }
+
+    pub fn contents_of(&self, index: &[TimelessIndex]) -> Option<Vec<ViewIndex>> {
+        let index = if let &[index] = index { index } else { None? };
+
+        let timed =
+        self.cells
+            .into_timed(index)
+            .ok()?;
+
+        Some(
+            self.cells
+                .into_timeless(timed - 1)
+                .map(ViewIndex::Ground)
+                .map(|index| vec![index])
+                .unwrap_or(vec![])
+        )
+    }
+
+    pub fn rename(&mut self, cell: &ViewIndex, new_data: Data) -> Result<(), Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get_mut(index)
+            .map(|cell| *cell = new_data)
+            .map_err(|_| Error::NoSuchCell(cell.clone()))
+    }
+}
+
+impl<Data: Clone> Tower<Data> {
+    pub fn cell(&self, cell: &ViewIndex) -> Result<Cell<Data>, Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get(index)
+            .map(|data| Cell::Ground(data.clone()))
+            .map_err(Error::IndexError)
+    }
+
+    pub fn cell_by_id(&self, id: CellId) -> Result<Cell<Data>, Error> {
+        self.cell(&self.index_of(id)?)
+    }
+}
+
+// IMPL: Utils
+//
+impl<Data> Tower<Data> {
+    fn extract(sel: &dyn super::viewing::Index) -> Result<TimelessIndex, Error> {
+        sel
+            .as_ground()
+            .ok_or(Error::TooMuchDepth(sel.level()))
+    }
+
+    fn valid_level(cell: &dyn super::viewing::Index) -> Result<TimelessIndex, Error> {
+        if let Some(index) = cell.as_ground() {
+            Ok(index)
+
+        } else {
+            Err(Error::TooMuchDepth(cell.level()))
+        }
+    }
+
+    /// The cell one step from `cell` in `direction`, where the tower is a nest of cells, from the
+    /// innermost one.
+    ///
+    pub fn neighbour(&self, cell: &ViewIndex, direction: Direction) -> Option<ViewIndex> {
+        let index = Self::valid_level(cell).ok()?;
+
+        let indices: Vec<_> = self.cells.timeless_indices().collect();
+        let at = indices.iter().position(|i| *i == index)?;
+
+        match direction {
+            Direction::In =>
+                at.checked_sub(1)
+                    .map(|at| ViewIndex::Ground(indices[at])),
+
+            Direction::Out =>
+                indices
+                    .get(at + 1)
+                    .map(|index| ViewIndex::Ground(*index)),
+
+            _ =>
+                None,
+        }
+    }
+
+    pub fn is_before(&self, before: &ViewIndex, after: &ViewIndex) -> bool {
+        let before = before.as_ground().unwrap();
+        let after = after.as_ground().unwrap();
+
+        self.cells.is_before(before, after).unwrap()
+    }
+
+    pub fn is_at_bottom(&self, cells: &Selection) -> Result<bool, Error> {
+        let index = Self::valid_level(cells)?;
+
+        Ok(self.cells.into_timeless(self.cells.last_index()).unwrap() == index)
+    }
+}
+
+impl<Data: Clone> Tower<Data> {
+    pub fn deep_copy(&self, level: usize) -> Result<Tail<Data>, Error> {
+        if level == 0 {
+            Ok(Tail::Tower(self.clone()))
+
+        } else {
+            Err(Error::TooMuchDepth(level))
+        }
+    }
+
+    /// Restricts the tower to `keep`, which must be a non-empty, contiguous part of the tower.
+    ///
+    pub(in super) fn restrict(&self, keep: &[ViewIndex]) -> Result<(Self, HashMap<ViewIndex, ViewIndex>), Error> {
+        let mut kept = vec![];
+
+        for cell in keep {
+            let index = Self::valid_level(cell)?;
+
+            self.cells
+                .get(index)
+                .map_err(|_| Error::NoSuchCell(cell.clone()))?;
+
+            kept.push(index);
+        }
+
+        let positions: Vec<_> =
+        self.cells
+            .timeless_indices()
+            .enumerate()
+            .filter(|(_, index)| kept.contains(index))
+            .map(|(pos, _)| pos)
+            .collect();
+
+        if positions.is_empty() {
+            return Err(Error::CannotRestrictToNothing);
+
+        } else if positions.windows(2).any(|pair| pair[1] != pair[0] + 1) {
+            return Err(Error::CannotRestrictDisconnected(keep.to_vec()));
+        }
+
+        let (old_indices, data): (Vec<_>, Vec<_>) =
+        self.cells
+            .iter_timeless_indices()
+            .filter(|(index, _)| kept.contains(index))
+            .map(|(index, data)| (index, data.clone()))
+            .unzip();
+
+        let cells = TracingVec::from(data);
+
+        let ids =
+        old_indices
+            .iter()
+            .zip(cells.timeless_indices())
+            .map(|(old, new)| (new, self.ids[old]))
+            .collect();
+
+        let mapping =
+        old_indices
+            .into_iter()
+            .zip(cells.timeless_indices())
+            .map(|(old, new)| (ViewIndex::Ground(old), ViewIndex::Ground(new)))
+            .collect();
+
+        Ok((Self { cells, ids, next_id: self.next_id }, mapping))
+    }
+}
+
+// IMPL: Selections
+//
+impl<Data> Tower<data::Selectable<Data>> {
+    pub fn select(&mut self, cell: &ViewIndex) -> Result<Option<Selection>, Error> {
+        let index = Self::valid_level(cell)?;
+
+        let selected =
+        self.cells
+            .get_mut(index)
+            .map_err(|_| Error::NoSuchCell(cell.clone()))?
+            .selected();
+
+        self.unselect_all(0);
+
+        if !selected {
+            self.cells
+                .get_mut(index)
+                .unwrap()
+                .set_selected(true);
+
+            Ok(Some(Selection::Ground(index)))
+
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// A tower allows only a single selected cell, so the selection is kept as it is.
+    ///
+    pub fn select_all(&mut self) -> Option<Selection> {
+        self.selected_cells()
+    }
+
+    /// A tower allows only a single selected cell, so this is just [`Self::select`].
+    ///
+    pub fn select_range(&mut self, cell: &ViewIndex) -> Result<Option<Selection>, Error> {
+        self.select(cell)
+    }
+
+    pub fn select_unchecked(&mut self, cell: &ViewIndex) -> Result<(), Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get_mut(index)
+            .map_err(|_| Error::NoSuchCell(cell.clone()))?
+            .select();
+
+        Ok(())
+    }
+
+    /// Moves the keyboard focus to `cell`.
+    ///
+    pub fn focus(&mut self, cell: &ViewIndex) -> Result<(), Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get(index)
+            .map_err(|_| Error::NoSuchCell(cell.clone()))?;
+
+        self.unfocus_all();
+
+        self.cells
+            .get_mut(index)
+            .unwrap()
+            .focus();
+
+        Ok(())
+    }
+
+    pub fn unfocus_all(&mut self) {
+        self.cells
+            .iter_mut()
+            .for_each(|cell| cell.unfocus())
+    }
+
+    pub fn highlight(&mut self, cell: &ViewIndex, highlight: data::Highlight) -> Result<(), Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get_mut(index)
+            .map(|cell| cell.highlight(highlight))
+            .map_err(|_| Error::NoSuchCell(cell.clone()))
+    }
+
+    pub fn hover(&mut self, cell: &ViewIndex) -> Result<(), Error> {
+        let index = Self::valid_level(cell)?;
+
+        self.cells
+            .get_mut(index)
+            .map(|cell| cell.hover())
+            .map_err(|_| Error::NoSuchCell(cell.clone()))
+    }
+
+    pub fn unhighlight_all(&mut self) {
+        self.cells
+            .iter_mut()
+            .for_each(|cell| cell.unhighlight())
+    }
+
+    pub fn focused_cell(&self) -> Option<ViewIndex> {
+        self.cells
+            .iter_timeless_indices()
+            .find(|(_, cell)| cell.focused())
+            .map(|(index, _)| ViewIndex::Ground(index))
+    }
+
+    pub fn unselect_all(&mut self, _max_depth: usize) {
+        self.cells
+            .iter_mut()
+            .for_each(|cell| cell.unselect())
+    }
+
+    pub fn selected_cells(&self) -> Option<Selection> {
+        self.cells
+            .iter_timeless_indices()
+            .filter(|(_, cell)| cell.selected())
+            .map(|(index, _)| Selection::Ground(index))
+            .next()
+    }
+
+}
+
+impl<Data: Clone> Tower<data::Selectable<Data>> {
+    pub fn retain_selected(&self) -> Result<Option<Self>, Error> {
+        if let Some(sel) = self.selected_cells() {
+            let (mut this, _) = self.restrict(&sel.as_cells())?;
+
+            this.unselect_all(0);
+
+            Ok(Some(this))
+
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+
+// IMPL: Layout
+//
+impl<Data: Named> Tower<Data> {
+    pub(in super) fn layout_node(&self, levels: &Levels) -> layout::Node {
+        let mut tower = self.cells.iter_timeless_indices();
+
+        let cell = |(index, data): (TimelessIndex, &Data), min_width: u16, content: Option<layout::Node>| {
+            let label = data.name();
+            let width = (crate::utils::text_width(&label) + 2 * PADDING).max(min_width);
+
+            let node = layout::Node::Cell {
+                index: ViewIndex::Ground(index),
+                label,
+                width,
+                content: content.map(Box::new),
+            };
+
+            (width, node)
+        };
+
+        let (mut width, mut downmost_cell) = cell(tower.next().unwrap(), 0, None);
+
+        for top in tower {
+            let (new_width, new_downmost_cell) =
+            cell(top, width + 2 * PADDING, Some(layout::Node::container(downmost_cell, None, PADDING)));
+
+            width = new_width + 2 * PADDING;
+            downmost_cell = new_downmost_cell;
+        }
+
+        layout::Node::level(self.level(), downmost_cell, levels)
+    }
+}
+
+
+#[cfg(feature = "gui")]
+pub mod tower {
+    use std::collections::HashMap;
+
+    use crate::opetope::tower::*;
+
+
+
+    impl<Data> Tower<Data> {
+        /// Collects the cells of the tower.
+        ///
+        pub(in crate::opetope) fn cells_by_index<'t>(&'t mut self, cells: &mut HashMap<ViewIndex, &'t mut Data>) {
+            cells.extend(
+                self.cells
+                    .iter_mut_timeless_indices()
+                    .map(|(index, data)| (ViewIndex::Ground(index), data))
+            );
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::testing::*;
+
+
+    #[test]
+    fn restricting_to_disconnected_cells_fails() {
+        let diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β");
+
+        let keep = vec![index(&diagram, "0"), index(&diagram, "b")];
+
+        assert_eq![
+            diagram.prev().restrict(&keep).map(|_| ()),
+            Err(Error::CannotRestrictDisconnected(keep)),
+        ];
+    }
+
+    #[test]
+    fn restricting_to_a_segment_keeps_its_cells() {
+        let diagram = opetope("extrude 0 as a wrap α\nextrude a as b wrap β");
+
+        let keep = vec![index(&diagram, "a"), index(&diagram, "b")];
+
+        let (restricted, _) = diagram.prev().restrict(&keep).unwrap();
+
+        let names =
+        restricted
+            .indices()
+            .iter()
+            .map(|index| restricted.cell(index).unwrap().data().name())
+            .collect::<Vec<_>>();
+
+        assert_eq![names, ["a", "b"]];
+    }
+}
@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+
+use tracing_vec::*;
+use serde::{ Serialize, Deserialize };
+
+
+
+/// Address of a node of a `TracingTree`. Unlike a path of timeless indices, it doesn't change
+/// when the node is moved into or out of a group, and it's never reused for another node.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Handle(usize);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Link {
+    parent: Option<Handle>,
+    /// Index of the node in the cell space of its parent, or of the roots.
+    ///
+    index: TimelessIndex,
+
+    children: Option<TracingVec<Handle>>,
+
+    id: u64,
+}
+
+/// Tree of ends and groups, where the contents of each group, as well as the roots, form a cell
+/// space ordered by a `TracingVec`. The cell spaces only hold handles, so that grouping and
+/// ungrouping moves handles instead of whole subtrees, while nodes are still addressed by paths of
+/// timeless indices.
+///
+/// The values are kept apart from the structure of the tree, so that all of them can be borrowed
+/// mutably at once, see `TracingTree::nodes_mut`.
+///
+/// Removed nodes leave their slots behind, since handles are never reused. The tree thus grows
+/// with every node ever created in it, until it's saved and loaded again, see `format::File`.
+///
+/// Each node is numbered when it's created. Unlike handles, the numbers can be saved along with
+/// the values and given back to the nodes of another tree, see `TracingTree::set_id`.
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracingTree<T> {
+    vals: Vec<Option<T>>,
+    links: Vec<Link>,
+
+    roots: TracingVec<Handle>,
+
+    next_id: u64,
+    ids: HashMap<u64, Handle>,
+}
+
+
+/// Node of a tree, which can be walked down to its contents.
+///
+#[derive(Debug)]
+pub struct NodeRef<'t, T> {
+    tree: &'t TracingTree<T>,
+    handle: Handle,
+}
+
+/// Mutable borrow of the value of a node, along with the nodes nested in it.
+///
+#[derive(Debug)]
+pub struct NodeMut<'t, T> {
+    pub handle: Handle,
+    pub val: &'t mut T,
+
+    pub children: Option<Vec<(TimelessIndex, Self)>>,
+}
+
+
+
+impl<T> Default for TracingTree<T> {
+    fn default() -> Self {
+        Self {
+            vals: vec![],
+            links: vec![],
+
+            roots: fill![],
+
+            next_id: 0,
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Clone for NodeRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeRef<'_, T> {}
+
+
+// IMPL: Accessing
+//
+impl<T> TracingTree<T> {
+    pub const fn roots(&self) -> &TracingVec<Handle> {
+        &self.roots
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.vals
+            .get(handle.0)?
+            .as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.vals
+            .get_mut(handle.0)?
+            .as_mut()
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// The number given to the node when it was created.
+    ///
+    pub fn id(&self, handle: Handle) -> Option<u64> {
+        self.get(handle)?;
+
+        Some(self.links[handle.0].id)
+    }
+
+    /// The number to be given to the next node.
+    ///
+    pub const fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub fn find(&self, id: u64) -> Option<Handle> {
+        self.ids
+            .get(&id)
+            .copied()
+    }
+
+    pub fn at(&self, path: &[TimelessIndex]) -> Option<&T> {
+        self.get(self.handle(path)?)
+    }
+
+    pub fn at_mut(&mut self, path: &[TimelessIndex]) -> Option<&mut T> {
+        let handle = self.handle(path)?;
+
+        self.get_mut(handle)
+    }
+
+    /// The node at the end of the path from the roots.
+    ///
+    pub fn handle(&self, path: &[TimelessIndex]) -> Option<Handle> {
+        let (&first, rest) = path.split_first()?;
+
+        let mut handle =
+        *self.roots
+            .get(first)
+            .ok()?;
+
+        for &seg in rest {
+            handle =
+            *self.children(handle)?
+                .get(seg)
+                .ok()?;
+        }
+
+        Some(handle)
+    }
+
+    /// The path from the roots to the node.
+    ///
+    pub fn path(&self, handle: Handle) -> Option<Vec<TimelessIndex>> {
+        let mut path = vec![];
+        let mut node = handle;
+
+        loop {
+            let (parent, index) = self.locate(node)?;
+
+            path.push(index);
+
+            match parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+
+        path.reverse();
+
+        Some(path)
+    }
+
+    pub fn parent(&self, handle: Handle) -> Option<Handle> {
+        self.links
+            .get(handle.0)?
+            .parent
+    }
+
+    /// The cell space of the group, or `None` for an end.
+    ///
+    pub fn children(&self, handle: Handle) -> Option<&TracingVec<Handle>> {
+        self.links
+            .get(handle.0)?
+            .children
+            .as_ref()
+    }
+
+    pub fn is_group(&self, handle: Handle) -> bool {
+        self.children(handle).is_some()
+    }
+
+    /// The cell space of the group at the end of the path, the roots for an empty path.
+    ///
+    pub fn cell_space(&self, path: &[TimelessIndex]) -> Option<&TracingVec<Handle>> {
+        if path.is_empty() {
+            Some(&self.roots)
+
+        } else {
+            self.children(self.handle(path)?)
+        }
+    }
+
+    pub fn node(&self, handle: Handle) -> Option<NodeRef<'_, T>> {
+        self.get(handle)?;
+
+        Some(NodeRef { tree: self, handle })
+    }
+
+    /// Nodes of the outermost cell space.
+    ///
+    pub fn nodes(&self) -> Vec<(TimelessIndex, NodeRef<'_, T>)> {
+        self.nodes_of(&self.roots)
+    }
+
+    fn nodes_of<'t>(&'t self, cell_space: &TracingVec<Handle>) -> Vec<(TimelessIndex, NodeRef<'t, T>)> {
+        cell_space
+            .iter_timeless_indices()
+            .map(|(index, &handle)| (index, NodeRef { tree: self, handle }))
+            .collect()
+    }
+
+    /// All nodes along with their paths, each group preceding its contents.
+    ///
+    pub fn paths(&self) -> Vec<(Vec<TimelessIndex>, Handle)> {
+        let mut paths = vec![];
+
+        self.paths_helper(&self.roots, &[], &mut paths);
+
+        paths
+    }
+
+    fn paths_helper(&self, cell_space: &TracingVec<Handle>, prefix: &[TimelessIndex], paths: &mut Vec<(Vec<TimelessIndex>, Handle)>) {
+        for (index, &handle) in cell_space.iter_timeless_indices() {
+            let mut path = prefix.to_vec();
+            path.push(index);
+
+            paths.push((path.clone(), handle));
+
+            if let Some(children) = self.children(handle) {
+                self.paths_helper(children, &path, paths);
+            }
+        }
+    }
+
+    /// Values of all nodes, in no particular order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.vals
+            .iter()
+            .flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.vals
+            .iter_mut()
+            .flatten()
+    }
+
+    /// Mutable borrows of all nodes at once, nested as in the tree.
+    ///
+    pub fn nodes_mut(&mut self) -> Vec<(TimelessIndex, NodeMut<'_, T>)> {
+        let Self { vals, links, roots, .. } = self;
+
+        let mut vals =
+        vals.iter_mut()
+            .enumerate()
+            .filter_map(|(i, val)| Some((Handle(i), val.as_mut()?)))
+            .collect();
+
+        Self::nodes_mut_helper(roots, links, &mut vals)
+    }
+
+    fn nodes_mut_helper<'t>(
+        cell_space: &TracingVec<Handle>,
+        links: &[Link],
+        vals: &mut HashMap<Handle, &'t mut T>,
+    ) -> Vec<(TimelessIndex, NodeMut<'t, T>)>
+    {
+        cell_space
+            .iter_timeless_indices()
+            .map(|(index, &handle)| {
+                let val = vals.remove(&handle).unwrap();
+
+                let children =
+                links[handle.0]
+                    .children
+                    .as_ref()
+                    .map(|children| Self::nodes_mut_helper(children, links, vals));
+
+                (index, NodeMut { handle, val, children })
+            })
+            .collect()
+    }
+
+    /// The group containing the node, if any, and the index of the node in its cell space.
+    ///
+    fn locate(&self, handle: Handle) -> Option<(Option<Handle>, TimelessIndex)> {
+        self.get(handle)?;
+
+        let Link { parent, index, .. } = self.links[handle.0];
+
+        Some((parent, index))
+    }
+}
+
+// IMPL: Editing
+//
+impl<T> TracingTree<T> {
+    /// Appends the node to the contents of `owner`, or to the roots. An end becomes a group.
+    ///
+    pub fn push(&mut self, owner: Option<Handle>, val: T) -> (TimelessIndex, Handle) {
+        self.push_as(owner, val, None)
+    }
+
+    /// Appends the node like `push`, numbered `id` instead of the next number, as when restoring a
+    /// saved number. Fails if another node has the number.
+    ///
+    pub fn push_numbered(&mut self, owner: Option<Handle>, val: T, id: u64) -> Option<(TimelessIndex, Handle)> {
+        if self.ids.contains_key(&id) {
+            return None;
+        }
+
+        Some(self.push_as(owner, val, Some(id)))
+    }
+
+    fn push_as(&mut self, owner: Option<Handle>, val: T, id: Option<u64>) -> (TimelessIndex, Handle) {
+        let handle = self.next_handle();
+
+        let space = self.space_mut(owner);
+        space.push(handle);
+
+        let index = space.into_timeless(space.last_index()).unwrap();
+
+        self.alloc(val, owner, index, id);
+
+        (index, handle)
+    }
+
+    /// Inserts the node into the cell space of `next`, right before it.
+    ///
+    pub fn insert_before(&mut self, next: Handle, val: T) -> Option<(TimelessIndex, Handle)> {
+        let (owner, at) = self.locate(next)?;
+
+        let handle = self.next_handle();
+
+        // NOTE: The index was just located, so inserting can't fail.
+        //
+        let space = self.space_mut(owner);
+        let timed = space.try_insert_before(at, handle).unwrap();
+
+        let index = space.into_timeless(timed).unwrap();
+
+        self.alloc(val, owner, index, None);
+
+        Some((index, handle))
+    }
+
+    /// Inserts the node into the cell space of `prev`, right after it.
+    ///
+    pub fn insert_after(&mut self, prev: Handle, val: T) -> Option<(TimelessIndex, Handle)> {
+        let (owner, at) = self.locate(prev)?;
+
+        let handle = self.next_handle();
+
+        let space = self.space_mut(owner);
+        let timed = space.try_insert_after(at, handle).unwrap();
+
+        let index = space.into_timeless(timed).unwrap();
+
+        self.alloc(val, owner, index, None);
+
+        Some((index, handle))
+    }
+
+    /// Replaces the `members` of the cell space of `owner`, or of the roots, by a new group of
+    /// them, at the place of the last one. The members keep their handles.
+    ///
+    pub fn group(&mut self, owner: Option<Handle>, members: &[TimelessIndex], val: T) -> Result<(TimelessIndex, Handle), IndexError> {
+        let group = self.next_handle();
+
+        let mut grouped = vec![];
+
+        let space = self.space_mut(owner);
+
+        let timed =
+        space.try_replace_at_last_with(members.to_vec(), |handles| {
+            grouped = handles;
+
+            group
+        })?;
+
+        let index = space.into_timeless(timed).unwrap();
+
+        let grouped = TracingVec::from(grouped);
+
+        for (inner, &member) in grouped.iter_timeless_indices() {
+            self.links[member.0].parent = Some(group);
+            self.links[member.0].index = inner;
+        }
+
+        self.alloc(val, owner, index, None);
+        self.links[group.0].children = Some(grouped);
+
+        Ok((index, group))
+    }
+
+    /// Dissolves the group into its cell space, where its contents take its place, keeping their
+    /// handles.
+    ///
+    /// Returns the index of each of the contents within the group and within the cell space.
+    ///
+    pub fn ungroup(&mut self, group: Handle) -> Option<Vec<(TimelessIndex, TimelessIndex)>> {
+        let (owner, at) = self.locate(group)?;
+
+        let children = self.links[group.0].children.take()?;
+
+        let space = self.space_mut(owner);
+        let mut moved = vec![];
+
+        for (inner, &handle) in children.iter_timeless_indices() {
+            let timed = space.try_insert_before(at, handle).unwrap();
+
+            moved.push((inner, space.into_timeless(timed).unwrap()));
+        }
+
+        space.try_remove(at).unwrap();
+
+        for (&handle, &(_, index)) in children.iter().zip(&moved) {
+            self.links[handle.0].parent = owner;
+            self.links[handle.0].index = index;
+        }
+
+        self.links[group.0].parent = None;
+        self.vals[group.0] = None;
+
+        self.ids.remove(&self.links[group.0].id);
+
+        Some(moved)
+    }
+
+    /// Renumbers the node, as when restoring a saved number. Fails if another node has the number.
+    ///
+    /// Numbers given to nodes created later stay above all the numbers in the tree.
+    ///
+    pub fn set_id(&mut self, handle: Handle, id: u64) -> bool {
+        if !self.contains(handle) || matches![self.find(id), Some(other) if other != handle] {
+            return false;
+        }
+
+        self.ids.remove(&self.links[handle.0].id);
+        self.ids.insert(id, handle);
+
+        self.links[handle.0].id = id;
+        self.next_id = self.next_id.max(id + 1);
+
+        true
+    }
+
+    /// Skips the numbers below `next_id`, so that they aren't given to nodes created later.
+    ///
+    pub fn reserve_ids(&mut self, next_id: u64) {
+        self.next_id = self.next_id.max(next_id);
+    }
+
+    /// Removes the node along with its contents. A group left without contents becomes an end.
+    ///
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let (owner, at) = self.locate(handle)?;
+
+        let space = self.space_mut(owner);
+        space.try_remove(at).unwrap();
+
+        let emptied = space.iter().next().is_none();
+
+        if let (Some(owner), true) = (owner, emptied) {
+            self.links[owner.0].children = None;
+        }
+
+        self.free(handle)
+    }
+
+    fn free(&mut self, handle: Handle) -> Option<T> {
+        if let Some(children) = self.links[handle.0].children.take() {
+            for &child in children.iter() {
+                self.free(child);
+            }
+        }
+
+        self.links[handle.0].parent = None;
+
+        self.ids.remove(&self.links[handle.0].id);
+
+        self.vals[handle.0].take()
+    }
+
+    /// The handle of the node allocated next, so that it can be placed in a cell space first.
+    ///
+    fn next_handle(&self) -> Handle {
+        Handle(self.vals.len())
+    }
+
+    /// Stores the node, numbered `id` or the next number.
+    ///
+    fn alloc(&mut self, val: T, parent: Option<Handle>, index: TimelessIndex, id: Option<u64>) -> Handle {
+        let id = id.unwrap_or(self.next_id);
+        self.next_id = self.next_id.max(id + 1);
+
+        let handle = self.next_handle();
+
+        self.vals.push(Some(val));
+        self.links.push(Link { parent, index, children: None, id });
+        self.ids.insert(id, handle);
+
+        handle
+    }
+
+    fn space_mut(&mut self, owner: Option<Handle>) -> &mut TracingVec<Handle> {
+        match owner {
+            Some(owner) => self.links[owner.0].children.get_or_insert_with(|| fill![]),
+            None => &mut self.roots,
+        }
+    }
+}
+
+impl<'t, T> NodeRef<'t, T> {
+    pub const fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    pub fn val(&self) -> &'t T {
+        self.tree
+            .get(self.handle)
+            .unwrap()
+    }
+
+    pub fn id(&self) -> u64 {
+        self.tree
+            .id(self.handle)
+            .unwrap()
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.tree.is_group(self.handle)
+    }
+
+    /// The contents of the group, or `None` for an end.
+    ///
+    pub fn children(&self) -> Option<Vec<(TimelessIndex, Self)>> {
+        self.tree
+            .children(self.handle)
+            .map(|children| self.tree.nodes_of(children))
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Tree = TracingTree<&'static str>;
+
+
+    /// Values of the tree, each group preceding its contents.
+    ///
+    fn values(tree: &Tree) -> Vec<&'static str> {
+        tree.paths()
+            .into_iter()
+            .map(|(_, handle)| *tree.get(handle).unwrap())
+            .collect()
+    }
+
+    /// Checks that each node is found at its path, and its path is found from it.
+    ///
+    fn assert_paths(tree: &Tree) {
+        for (path, handle) in tree.paths() {
+            assert_eq![tree.path(handle), Some(path.clone())];
+            assert_eq![tree.handle(&path), Some(handle)];
+        }
+    }
+
+    /// A root `g` grouping the roots `a` and `b`, followed by a root `c`.
+    ///
+    fn grouped() -> (Tree, [Handle; 4]) {
+        let mut tree = Tree::default();
+
+        let (a_index, a) = tree.push(None, "a");
+        let (b_index, b) = tree.push(None, "b");
+        let (_, c) = tree.push(None, "c");
+
+        let (_, g) = tree.group(None, &[a_index, b_index], "g").unwrap();
+
+        (tree, [a, b, c, g])
+    }
+
+
+    #[test]
+    fn pushed_nodes_are_found() {
+        let mut tree = Tree::default();
+
+        let (a_index, a) = tree.push(None, "a");
+        let (_, b) = tree.push(None, "b");
+        let (c_index, c) = tree.push(Some(a), "c");
+
+        assert_eq![values(&tree), vec!["a", "c", "b"]];
+        assert_paths(&tree);
+
+        assert_eq![tree.path(c), Some(vec![a_index, c_index])];
+        assert_eq![tree.parent(c), Some(a)];
+        assert![tree.is_group(a) && !tree.is_group(b)];
+
+        let (_, x) = tree.insert_before(b, "x").unwrap();
+        let (_, y) = tree.insert_after(c, "y").unwrap();
+
+        assert_eq![values(&tree), vec!["a", "c", "y", "x", "b"]];
+        assert_eq![tree.parent(x), None];
+        assert_eq![tree.parent(y), Some(a)];
+        assert_paths(&tree);
+
+        assert_eq![[a, b, c, x, y].map(|node| tree.id(node).unwrap()), [0, 1, 2, 3, 4]];
+    }
+
+    #[test]
+    fn grouping_keeps_the_handles() {
+        let (tree, [a, b, c, g]) = grouped();
+
+        assert_eq![values(&tree), vec!["g", "a", "b", "c"]];
+        assert_eq![[a, b, c].map(|node| tree.parent(node)), [Some(g), Some(g), None]];
+        assert_eq![[a, b, c, g].map(|node| tree.get(node).copied()), [Some("a"), Some("b"), Some("c"), Some("g")]];
+        assert_paths(&tree);
+
+        let space = tree.children(g).unwrap();
+
+        assert_eq![space.iter().copied().collect::<Vec<_>>(), vec![a, b]];
+    }
+
+    #[test]
+    fn ungrouping_moves_the_contents_out() {
+        let (mut tree, [a, b, c, g]) = grouped();
+
+        let inner = tree.children(g).unwrap().timeless_indices().collect::<Vec<_>>();
+
+        let moved = tree.ungroup(g).unwrap();
+
+        assert_eq![moved.iter().map(|&(inner, _)| inner).collect::<Vec<_>>(), inner];
+        assert_eq![[a, b].map(|node| tree.path(node)), [Some(vec![moved[0].1]), Some(vec![moved[1].1])]];
+
+        assert_eq![values(&tree), vec!["a", "b", "c"]];
+        assert_eq![[a, b, c].map(|node| tree.parent(node)), [None, None, None]];
+        assert![!tree.contains(g)];
+        assert_paths(&tree);
+
+        assert_eq![tree.ungroup(a), None];
+    }
+
+    #[test]
+    fn removing_takes_the_contents_along() {
+        let (mut tree, [a, b, c, g]) = grouped();
+
+        let (_, d) = tree.push(Some(c), "d");
+
+        assert_eq![tree.remove(d), Some("d")];
+        assert![!tree.is_group(c)];
+
+        assert_eq![tree.remove(g), Some("g")];
+        assert_eq![values(&tree), vec!["c"]];
+        assert_paths(&tree);
+
+        for node in [a, b, d, g] {
+            assert![!tree.contains(node)];
+            assert_eq![tree.path(node), None];
+        }
+
+        assert_eq![tree.remove(g), None];
+    }
+}
@@ -2,17 +2,26 @@ use std::ops;
 use itertools::Itertools;
 
 use tracing_vec::*;
-use crate::components::opetope::{
+use crate::opetope::{
     ViewIndex,
 
     MetaCell,
-    diagram::Cell,
+    tree::{ TracingTree, NodeRef },
 
-    data,
-    Message,
+    data::DATA_HEIGHT,
+    format::Named,
+    layout::{ self, Node },
 };
 
-use crate::model::Render;
+#[cfg(feature = "gui")]
+use std::collections::HashMap;
+
+#[cfg(feature = "gui")]
+use crate::opetope::{ data, Message };
+
+#[cfg(feature = "gui")]
+use crate::model::{ Render, Zoom, Levels, Fold };
+#[cfg(feature = "gui")]
 use crate::behavior;
 
 use crate::styles::container::{ PADDING, LINE_WIDTH, cell::SPACING };
@@ -21,22 +30,80 @@ pub const LINE_LEN: u16 = 3 * SPACING / 2;
 
 
 
+#[cfg(feature = "gui")]
 pub mod routines {
     use super::*;
 
-    pub fn pad<'e>(e: impl Into<iced::Element<'e, Message>>) -> iced::Element<'e, Message> {
-        iced::Row::with_children(vec![padder(), e.into(), padder()])
-        .spacing(PADDING)
-        .into()
-    }
+    /// Builds the widgets of a laid out opetope, taking the state of each cell from `cells`.
+    ///
+    pub fn view_node<'e, Data: behavior::SimpleView>(
+        node: &Node,
+        cells: &mut HashMap<ViewIndex, &'e mut data::Selectable<Data>>,
+
+        render: Render,
+        zoom: Zoom,
+        levels: &Levels,
+    ) -> iced::Element<'e, Message>
+    {
+        let view = |child: &Node, cells: &mut HashMap<_, _>| view_node(child, cells, render, zoom, levels);
+
+        match node {
+            Node::Space { width, height } =>
+                iced::Space::new(zoom.scale(*width).into(), zoom.scale(*height).into()).into(),
 
-    fn padder() -> iced::Element<'static, Message> {
-        iced::Space::new(0.into(), 0.into()).into()
+            Node::Line(height) =>
+                view_line(*height, zoom),
+
+            Node::Column(children) =>
+                iced::Column::with_children(children.iter().map(|child| view(child, cells)).collect())
+                    .align_items(iced::Align::Center)
+                    .into(),
+
+            Node::Row { children, spacing, align } =>
+                iced::Row::with_children(children.iter().map(|child| view(child, cells)).collect())
+                    .spacing(zoom.scale(*spacing))
+                    .align_items(
+                        match align {
+                            layout::Align::Start => iced::Align::Start,
+                            layout::Align::Center => iced::Align::Center,
+                        }
+                    )
+                    .into(),
+
+            Node::Container { child, width, padding } => {
+                let container =
+                iced::Container::new(view(child, cells))
+                    .align_x(iced::Align::Center)
+                    .padding(zoom.scale(*padding));
+
+                match width {
+                    Some(width) => container.width(zoom.scale(*width).into()).into(),
+                    None => container.into(),
+                }
+            },
+
+            Node::Cell { index, width, content, .. } => {
+                let content = content.as_ref().map(|content| view(content, cells));
+
+                match cells.remove(index) {
+                    Some(cell) => cell.view_cell(index.clone(), *width, content, render, zoom).1,
+
+                    None => content.unwrap_or_else(|| iced::Space::new(0.into(), 0.into()).into()),
+                }
+            },
+
+            Node::Level { level, child, .. } =>
+                match levels.fold(*level) {
+                    Fold::Thumbnail => view_node(child, cells, Render::Static, zoom.thumbnail(), levels),
+
+                    _ => view(child, cells),
+                },
+        }
     }
 
-    pub fn view_line(height: u16) -> iced::Element<'static, Message> {
+    pub fn view_line(height: u16, zoom: Zoom) -> iced::Element<'static, Message> {
         iced::Container::new(
-            iced::Space::new(LINE_WIDTH.into(), height.into())
+            iced::Space::new(zoom.scale(LINE_WIDTH).into(), zoom.scale(height).into())
         )
         .style(crate::styles::container::LINE)
         .into()
@@ -141,37 +208,31 @@ impl Spacer {
     }
 }
 
-/// Rendering
+/// Layout
 ///
 impl Spacer {
-    pub fn render<'e, Msg: 'e>(&self, items: &mut Vec<iced::Element<'e, Msg>>) -> iced::Element<'e, Msg> {
+    pub fn layout(&self, items: &mut Vec<Node>) -> Node {
         if self.inner.is_empty() {
-            let element = items.pop().unwrap();
+            let node = items.pop().unwrap();
 
-            iced::Container::new(element)
-                .align_x(iced::Align::Center)
-                .width(self.width().into())
-                .into()
+            Node::container(node, Some(self.width()), 0)
 
         } else {
             let mut children = vec![];
 
             for space in self.inner.iter().rev() {
-                children.push(space.render(items));
+                children.push(space.layout(items));
 
                 if items.is_empty() {
                     break;
                 }
             }
 
-            iced::Container::new(
-            iced::Row::with_children(children)
-                .align_items(iced::Align::Center)
-                .spacing(PADDING)
-                )
-                .align_x(iced::Align::Center)
-                .width(self.width().into())
-                .into()
+            Node::container(
+                Node::Row { children, spacing: PADDING, align: layout::Align::Center },
+                Some(self.width()),
+                0,
+            )
         }
     }
 }
@@ -213,35 +274,29 @@ index! {
 
 #[derive(Debug)]
 pub(in super) struct CellCoordinator<'op, Data> {
-    cell: &'op mut MetaCell<Data>,
+    cell: &'op MetaCell<Data>,
     addr: ViewIndex,
 
     inner: Option<Box<Self>>,
     upstream: Vec<Option<Self>>,
 }
 
-pub struct BreadthWalker<'op, Data, X, State> {
-    cells: Vec<CellCoordinator<'op, Data>>,
-
-    on_node: Option<&'op mut dyn FnMut(State, ViewIndex, &mut MetaCell<Data>) -> (X, State)>,
-    on_flatten: Option<&'op mut dyn FnMut(State, Vec<Option<X>>) -> State>,
-}
-
 #[allow(dead_code)]
 /// Instance creation
 ///
 impl<'op, Data> CellCoordinator<'op, Data> {
-    pub fn new(level: usize, cell_space: &'op mut TracingVec<Cell<Data>>) -> CellCoordinator<'op, Data> {
-        Self::collect_from(level, vec![], &mut cell_space.iter_mut_timeless_indices().collect())
+    pub fn new(level: usize, cells: &'op TracingTree<MetaCell<Data>>) -> CellCoordinator<'op, Data> {
+        Self::collect_from(level, vec![], &mut cells.nodes())
     }
 
     pub fn collect_from(
         level: usize,
         mut path: Vec<TimelessIndex>,
-        cell_space: &mut Vec<(TimelessIndex, &'op mut Cell<Data>)>,
+        cell_space: &mut Vec<(TimelessIndex, NodeRef<'op, MetaCell<Data>>)>,
     ) -> CellCoordinator<'op, Data>
     {
-        let (addr, cell) = cell_space.pop().unwrap();
+        let (addr, node) = cell_space.pop().unwrap();
+        let cell = node.val();
         let input_count = cell.input_count();
 
         let mut upstream = vec![];
@@ -250,7 +305,7 @@ impl<'op, Data> CellCoordinator<'op, Data> {
             let is_connected =
                 cell_space
                     .last()
-                    .map(|(_, next)| next.face().fill == cell.face().ends[i])
+                    .map(|(_, next)| next.val().face.fill == cell.face.ends[i])
                     .unwrap_or(false);
 
             if is_connected {
@@ -264,14 +319,12 @@ impl<'op, Data> CellCoordinator<'op, Data> {
         path.push(addr);
 
         let inner =
-        cell.content
-            .as_mut()
-            .map(|inner_space|
-                Box::new(Self::collect_from(level, path.clone(), &mut inner_space.iter_mut_timeless_indices().collect()))
+        node.children()
+            .map(|mut inner_space|
+                Box::new(Self::collect_from(level, path.clone(), &mut inner_space))
             );
 
 
-        let cell = &mut cell.meta;
         let addr = ViewIndex::Leveled { level, path };
 
         Self {
@@ -301,90 +354,22 @@ impl<Data> CellCoordinator<'_, Data> {
     }
 }
 
-/// Walking
-///
-impl<'op, Data> CellCoordinator<'op, Data> {
-    pub fn walk_breadth<X, State>(self) -> BreadthWalker<'op, Data, X, State> {
-        BreadthWalker {
-            cells: vec![self],
-
-            on_node: None,
-            on_flatten: None,
-        }
-    }
-}
-
-impl<'op, Data, X, State> BreadthWalker<'op, Data, X, State> {
-    pub fn on_node(&mut self, f: &'op mut impl FnMut(State, ViewIndex, &mut MetaCell<Data>) -> (X, State)) -> &mut Self {
-        self.on_node = Some(f);
-        self
-    }
-
-    pub fn on_flatten(&mut self, f: &'op mut impl FnMut(State, Vec<Option<X>>) -> State) -> &mut Self {
-        self.on_flatten = Some(f);
-        self
-    }
-
-    pub fn walk(self, state: State) -> State {
-        let Self { cells, mut on_node, mut on_flatten } = self;
-
-        let mut layers = vec![cells];
-
-        let mut state = Some(state);
-
-        while let Some(cells) = layers.pop() {
-            let mut data = vec![];
-            let mut new_layer = vec![];
-
-            for cell in cells {
-                let CellCoordinator { cell, addr, upstream, .. } = cell;
-
-                data.push(
-                    on_node.as_mut().map(|on_node| {
-                        let (ret, new_state) = on_node(state.take().unwrap(), addr, cell);
-
-                        state = Some(new_state);
-
-                        ret
-                    })
-                );
-                new_layer.extend(upstream.into_iter().filter(Option::is_some).map(Option::unwrap));
-            }
-
-            on_flatten.as_mut().map(|on_flatten| state = Some(on_flatten(state.take().unwrap(), data)));
-
-            if !new_layer.is_empty() {
-                layers.push(new_layer);
-            }
-        }
-
-        state.take().unwrap()
-    }
-}
-
-/// Viewing
+/// Layout
 ///
-impl<'op, Data: behavior::SimpleView> CellCoordinator<'op, data::Selectable<Data>> {
-    pub fn view(self, render: Render) -> iced::Element<'op, Message> {
+impl<'op, Data: Named> CellCoordinator<'op, Data> {
+    pub fn layout(self) -> Node {
         let widths = vec![fill![]; self.input_count()];
 
         let without_line =
-        self.render(widths, render).2;
+        self.layout_helper(widths).2;
 
-        iced::Column::new()
-            .push(without_line)
-            .push(routines::view_line(LINE_LEN))
-            .align_items(iced::Align::Center)
-            .into()
+        Node::Column(vec![without_line, Node::Line(LINE_LEN)])
     }
 
-    fn render(self, mut outer_widths: Vec<Spacer>, render: Render) -> (u16, Spacer, iced::Element<'op, Message>) {
+    fn layout_helper(self, mut outer_widths: Vec<Spacer>) -> (u16, Spacer, Node) {
         let mut widths = vec![];
         let mut heights = vec![];
 
-
-        // Rendering upstream subdiagrams
-        //
         let upstream =
         self.upstream
             .into_iter()
@@ -392,7 +377,7 @@ impl<'op, Data: behavior::SimpleView> CellCoordinator<'op, data::Selectable<Data
                 if let Some(up) = up {
                     let spaces = outer_widths.split_off(outer_widths.len() - up.input_count());
 
-                    let (height, width, up) = up.render(spaces, render);
+                    let (height, width, up) = up.layout_helper(spaces);
 
                     widths.push(width);
                     heights.push(height);
@@ -403,49 +388,32 @@ impl<'op, Data: behavior::SimpleView> CellCoordinator<'op, data::Selectable<Data
                     widths.push(outer_widths.pop().unwrap());
                     heights.push(0);
 
-                    routines::view_line(0)
+                    Node::Line(0)
                 }
             )
             .collect_vec();
 
-
-        // Inserting output lines to adjust heights
-        //
         let max_height = heights.iter().max().copied().unwrap_or(0) + LINE_LEN;
 
         let mut upstream =
         upstream
             .into_iter()
             .zip(heights)
-            .map(|(up, height)|
-                iced::Column::new()
-                    .push(up)
-                    .push(routines::view_line(max_height - height))
-                    .align_items(iced::Align::Center)
-                    .into()
-            )
+            .map(|(up, height)| Node::Column(vec![up, Node::Line(max_height - height)]))
             .collect_vec();
 
-
-        // Rendering inner diagram
-        //
         widths.reverse();
         let mut flat_widths = widths.iter().map(Spacer::flatten).collect();
 
-        let (inner_height, inner_spacer, inner) =
+        let (inner_height, mut spacer, inner) =
         if let Some(inner) = self.inner {
-            let (height, mut width, mut inner) = inner.render(flat_widths, render);
+            let (height, mut width, inner) = inner.layout_helper(flat_widths);
 
-            inner =
-            iced::Column::new()
-                .push(inner)
-                .push(routines::view_line(LINE_LEN))
-                .align_items(iced::Align::Center)
-                .into();
+            let inner = Node::Column(vec![inner, Node::Line(LINE_LEN)]);
 
             width.pad(PADDING);
 
-            (height + LINE_LEN, width, Some(routines::pad(inner)))
+            (height + LINE_LEN, width, Some(Box::new(Node::pad(inner))))
 
         } else {
             flat_widths.reverse();
@@ -453,27 +421,19 @@ impl<'op, Data: behavior::SimpleView> CellCoordinator<'op, data::Selectable<Data
             (0, Spacer::group(0, flat_widths), None)
         };
 
+        let label = self.cell.data.name();
+        let width = (crate::utils::text_width(&label) + 2 * PADDING).max(spacer.width());
 
-        // Rendering the whole cell
-        //
-        let (data_height, mut spacer, this_cell) = self.cell.view(self.addr.clone(), inner, inner_spacer, render);
+        spacer.grow(width);
 
-        let height = max_height + inner_height + data_height;
+        let this_cell = Node::Cell { index: self.addr, label, width, content: inner };
 
+        let height = max_height + inner_height + DATA_HEIGHT;
 
-        // Spacing the upstream subdiagrams
-        //
-        let upstream = spacer.render(&mut upstream);
+        let upstream = spacer.layout(&mut upstream);
 
         spacer.extend(widths);
 
-        let diagram =
-        iced::Column::new()
-            .push(upstream)
-            .push(this_cell)
-            .align_items(iced::Align::Center)
-            .into();
-
-        (height, spacer, diagram)
+        (height, spacer, Node::Column(vec![upstream, this_cell]))
     }
 }
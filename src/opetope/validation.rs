@@ -0,0 +1,284 @@
+use std::fmt;
+
+use tracing_vec::*;
+
+use super::{ tree::NodeRef, Diagram, Face, MetaCell, Tail, viewing::{ ViewIndex, Index } };
+
+
+
+/// A broken invariant of a diagram, typically found in a hand-edited or corrupted file.
+///
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Violation {
+    NoSuchLine { cell: ViewIndex, line: ViewIndex },
+
+    EmptyGroup(ViewIndex),
+    NotTree { group: ViewIndex, outputs: usize },
+    LevelNotTree { level: usize, outputs: usize },
+
+    SourceCount { group: ViewIndex, expected: usize, found: usize },
+    SourceMismatch(ViewIndex),
+    TargetMismatch(ViewIndex),
+    EndOutsideFill { cell: ViewIndex, end: ViewIndex },
+}
+
+
+
+impl<Data> Diagram<Data> {
+    /// Checks that the diagram and all levels below it are consistent, i.e. that the cells of every
+    /// level form a tree, that every face refers to existing cells of the previous level with the
+    /// ends lying within the fill, and that the face of every group agrees with the tree of its
+    /// contents.
+    ///
+    /// The top level may be empty, as it is before anything is drawn in it.
+    ///
+    /// Reports all violations, ordered from the lowest level.
+    ///
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let mut violations = vec![];
+
+        self.collect_violations(true, &mut violations);
+
+        if violations.is_empty() {
+            Ok(())
+
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn collect_violations(&self, top: bool, violations: &mut Vec<Violation>) {
+        if let Tail::Diagram(prev) = self.prev() {
+            prev.collect_violations(false, violations);
+        }
+
+        let roots = self.cells().nodes();
+
+        let (_, outputs) = Self::boundary(&roots);
+
+        if outputs.len() != 1 && !(top && roots.is_empty()) {
+            violations.push(Violation::LevelNotTree { level: self.level(), outputs: outputs.len() });
+        }
+
+        self.check_space(roots, &[], violations);
+    }
+
+    fn check_space(&self, cell_space: Vec<(TimelessIndex, NodeRef<MetaCell<Data>>)>, prefix: &[TimelessIndex], violations: &mut Vec<Violation>) {
+        for (index, cell) in cell_space {
+            let mut path = prefix.to_vec();
+            path.push(index);
+
+            let this = ViewIndex::Leveled { level: self.level() - 1, path: path.clone() };
+
+            let face = cell.val().face();
+
+            let mut dangling = false;
+
+            for line in face.ends.iter().chain(Some(&face.fill)) {
+                if !self.prev().contains(line) || self.prev().level() != line.level() {
+                    violations.push(Violation::NoSuchLine { cell: this.clone(), line: line.clone() });
+
+                    dangling = true;
+                }
+            }
+
+            if !dangling {
+                for end in &face.ends {
+                    if !self.lies_within(end, &face.fill) {
+                        violations.push(Violation::EndOutsideFill { cell: this.clone(), end: end.clone() });
+                    }
+                }
+            }
+
+            if let Some(content) = cell.children() {
+                if content.is_empty() {
+                    violations.push(Violation::EmptyGroup(this));
+
+                } else {
+                    Self::check_group(this, face, &content, violations);
+
+                    self.check_space(content, &path, violations);
+                }
+            }
+        }
+    }
+
+    // NOTE: The face of a group is collected from its contents, see `Diagram::group`.
+    //
+    fn check_group(group: ViewIndex, face: &Face, content: &[(TimelessIndex, NodeRef<MetaCell<Data>>)], violations: &mut Vec<Violation>) {
+        let (ends, fills) = Self::boundary(content);
+
+        if let [fill] = fills[..] {
+            if &face.fill != fill {
+                violations.push(Violation::TargetMismatch(group.clone()));
+            }
+
+        } else {
+            violations.push(Violation::NotTree { group: group.clone(), outputs: fills.len() });
+        }
+
+        if face.ends.len() != ends.len() {
+            violations.push(Violation::SourceCount { group, expected: ends.len(), found: face.ends.len() });
+
+        } else if !face.ends.iter().all(|end| ends.contains(&end)) {
+            violations.push(Violation::SourceMismatch(group));
+        }
+    }
+
+    /// The ends and fills of the cells, except the lines connecting two of the cells.
+    ///
+    pub(in super) fn boundary<'c>(cells: &'c [(TimelessIndex, NodeRef<MetaCell<Data>>)]) -> (Vec<&'c ViewIndex>, Vec<&'c ViewIndex>) {
+        let mut ends: Vec<_> = cells.iter().flat_map(|(_, cell)| &cell.val().face().ends).collect();
+        let mut fills: Vec<_> = cells.iter().map(|(_, cell)| &cell.val().face().fill).collect();
+
+        fills.retain(|fill| {
+            if let Some(pos) = ends.iter().position(|end| end == fill) {
+                ends.remove(pos);
+
+                false
+
+            } else {
+                true
+            }
+        });
+
+        (ends, fills)
+    }
+
+    // NOTE: The tower is a chain of cells, each enclosing the ones before it. Above the tower, the
+    // ends lie in the contents of the fill.
+    //
+    fn lies_within(&self, end: &ViewIndex, fill: &ViewIndex) -> bool {
+        match (end, fill) {
+            (ViewIndex::Ground(_), ViewIndex::Ground(_)) =>
+                self.prev().is_before(end, fill),
+
+            (ViewIndex::Leveled { path: end, .. }, ViewIndex::Leveled { path: fill, .. }) =>
+                end.len() > fill.len() && end.starts_with(fill),
+
+            _ => false,
+        }
+    }
+}
+
+
+impl fmt::Display for Violation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoSuchLine { cell, line } =>
+                write![fmt, "Cell {} refers to {}, which is not a cell of the previous level", cell, line],
+
+            Self::EmptyGroup(group) =>
+                write![fmt, "Group {} has no contents", group],
+
+            Self::NotTree { group, outputs } =>
+                write![fmt, "Contents of group {} do not form a tree ({} outputs)", group, outputs],
+
+            Self::LevelNotTree { level, outputs } =>
+                write![fmt, "Cells of level {} do not form a tree ({} outputs)", level, outputs],
+
+            Self::SourceCount { group, expected, found } =>
+                write![fmt, "Group {} has {} sources, but its contents have {}", group, found, expected],
+
+            Self::SourceMismatch(group) =>
+                write![fmt, "Sources of group {} differ from the sources of its contents", group],
+
+            Self::TargetMismatch(group) =>
+                write![fmt, "Target of group {} differs from the target of its contents", group],
+
+            Self::EndOutsideFill { cell, end } =>
+                write![fmt, "Cell {} has the end {} outside of its fill", cell, end],
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opetope::{ data::Selectable, format::{ self, File, Named }, testing::*, Tower };
+
+    const PASSED: &str = "\
+        extrude 0 as a wrap α
+        extrude a as b wrap β
+        extrude α β as h wrap θ
+        pass h x";
+
+
+    /// The opetope written by the script, with its saved form edited.
+    ///
+    fn corrupted(script: &str, edit: impl FnOnce(&mut File)) -> Opetope {
+        let mut file = File::new(&opetope(script)).unwrap();
+
+        edit(&mut file);
+
+        file.into_diagram().unwrap()
+    }
+
+
+    #[test]
+    fn edited_opetopes_are_valid() {
+        for script in [
+            PASSED,
+            "sprout 0 as z wrap w",
+            "extrude 0 as a wrap α\nsplit α as s wrap top bot",
+            "extrude 0 as a wrap α\nsprout 0 as z wrap w\nextrude α w as h wrap θ\npass h x",
+            &format!["{}\nextrude x as y wrap ψ", PASSED],
+            &format!["{}\nsplit x as s wrap top bot", PASSED],
+            &format!["{}\nsprout α as z wrap w", PASSED],
+        ] {
+            assert_eq![opetope(script).validate(), Ok(()), "{}", script];
+        }
+    }
+
+    #[test]
+    fn empty_top_level_is_valid() {
+        let (_, tower) = Tower::init(Selectable::from_name("0".to_string()));
+        let diagram: Opetope = Diagram::new(Tail::Tower(tower)).unwrap();
+
+        assert_eq![diagram.validate(), Ok(())];
+
+        let read: Opetope = format::from_str(&format::to_string(&diagram).unwrap()).unwrap();
+
+        assert_eq![read.validate(), Ok(())];
+    }
+
+    #[test]
+    fn cells_of_a_level_form_a_tree() {
+        let diagram = corrupted("extrude 0 as a wrap α", |file| {
+            let mut parallel = file.levels[0].cells[0].clone();
+            parallel.name = "γ".to_string();
+            parallel.id = None;
+
+            file.levels[0].cells.push(parallel);
+        });
+
+        assert_eq![diagram.validate(), Err(vec![Violation::LevelNotTree { level: 1, outputs: 2 }])];
+    }
+
+    #[test]
+    fn tower_ends_lie_within_the_fill() {
+        let diagram = corrupted("extrude 0 as a wrap α", |file| {
+            file.levels[0].cells[0].face = format::Face { source: vec![1], target: 0 };
+        });
+
+        assert_eq![
+            diagram.validate(),
+            Err(vec![Violation::EndOutsideFill { cell: index(&diagram, "α"), end: index(&diagram, "a") }]),
+        ];
+    }
+
+    #[test]
+    fn ends_lie_within_the_fill() {
+        // NOTE: Level 1 is numbered h, α, β.
+        //
+        let diagram = corrupted(PASSED, |file| {
+            file.levels[1].cells[0].face = format::Face { source: vec![2], target: 1 };
+        });
+
+        assert_eq![
+            diagram.validate(),
+            Err(vec![Violation::EndOutsideFill { cell: index(&diagram, "x"), end: index(&diagram, "β") }]),
+        ];
+    }
+}
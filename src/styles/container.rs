@@ -1,5 +1,3 @@
-use iced::container;
-
 pub mod button {
     pub const WIDTH: f32 = 3.;
     pub const RADIUS: f32 = 10.;
@@ -16,228 +14,305 @@ pub mod color {
     pub const DESATURATE_PERCENT: f64 = 0.85;
     pub const LIGHTEN_PERCENT: f64 = 3.0;
 
-    pub const SELECTED: iced::Color = color![255, 154, 97];
+    #[cfg(feature = "gui")]
+    pub use self::gui::*;
+
+    #[cfg(feature = "gui")]
+    mod gui {
+        use super::rgb;
+
+        pub const SELECTED: iced::Color = color![rgb::SELECTED[0], rgb::SELECTED[1], rgb::SELECTED[2]];
+        pub const FOCUSED: iced::Color = color![rgb::FOCUSED[0], rgb::FOCUSED[1], rgb::FOCUSED[2]];
+        pub const END: iced::Color = color![rgb::END[0], rgb::END[1], rgb::END[2]];
+        pub const FILL: iced::Color = color![rgb::FILL[0], rgb::FILL[1], rgb::FILL[2]];
+        pub const BOUNDARY: iced::Color = color![rgb::BOUNDARY[0], rgb::BOUNDARY[1], rgb::BOUNDARY[2]];
+    }
+
+    /// Colors of the cells, usable without iced.
+    ///
+    pub mod rgb {
+        pub const LINE: [u8; 3] = [0, 0, 0];
+        pub const BORDER: [u8; 3] = [0, 0, 0];
+        pub const BACKGROUND: [u8; 3] = [255, 255, 255];
+        pub const TEXT: [u8; 3] = [0, 0, 0];
+
+        pub const SELECTED: [u8; 3] = [255, 154, 97];
+        pub const FOCUSED: [u8; 3] = [0, 121, 199];
+
+        /// Backgrounds of the cells in the face of a hovered or selected cell.
+        ///
+        pub const END: [u8; 3] = [140, 214, 140];
+        pub const FILL: [u8; 3] = [190, 150, 230];
+        pub const BOUNDARY: [u8; 3] = [240, 226, 150];
+    }
 }
 
 
 pub const PADDING: u16 = 8;
 pub const LINE_WIDTH: u16 = 2;
 
-pub const LINE: Style = Style {
-    kind: Kind::Line,
-    color: iced::Color::BLACK,
-};
 
+#[cfg(feature = "gui")]
+pub use self::gui::*;
 
+/// Styles of the iced widgets.
+///
+#[cfg(feature = "gui")]
+mod gui {
+    use iced::container;
 
-pub const CELL: Style = Style {
-    kind: Kind::Cell { selected: false },
-    color: iced::Color::BLACK,
-};
+    use super::{ cell, color };
 
-pub const SELECTED_CELL: Style = Style {
-    kind: Kind::Cell { selected: true },
-    color: iced::Color::BLACK,
-};
 
+    pub const LINE: Style = Style {
+        kind: Kind::Line,
+        color: iced::Color::BLACK,
+    };
 
-pub struct Tooltip;// TODO: Move to respective component
 
-pub struct Error;// TODO: Move to respective component
-pub struct PopUp;// TODO: Move to respective component
 
+    pub const CELL: Style = Style {
+        kind: Kind::Cell { selected: false, focused: false, highlight: None },
+        color: iced::Color::BLACK,
+    };
 
-pub struct Style {
-    kind: Kind,
-    color: iced::Color,
-}
+    pub const SELECTED_CELL: Style = Style {
+        kind: Kind::Cell { selected: true, focused: false, highlight: None },
+        color: iced::Color::BLACK,
+    };
 
-#[derive(Debug, Clone, Copy)]
-enum Kind {
-    Line,
-    Tooltip,
+    /// Outlines the cell with the keyboard focus.
+    ///
+    pub const FOCUSED_CELL: Style = Style {
+        kind: Kind::Cell { selected: false, focused: true, highlight: None },
+        color: iced::Color::BLACK,
+    };
 
-    Cell { selected: bool },
-}
+    pub const FOCUSED_SELECTED_CELL: Style = Style {
+        kind: Kind::Cell { selected: true, focused: true, highlight: None },
+        color: iced::Color::BLACK,
+    };
 
-impl Style {
-    pub fn cell(color: iced::Color) -> Self {
-        Self {
-            kind: Kind::Cell { selected: false },
-            color,
-        }
+
+    pub struct Tooltip;// TODO: Move to respective component
+
+    pub struct Error;// TODO: Move to respective component
+    pub struct PopUp;// TODO: Move to respective component
+
+
+    pub struct Style {
+        kind: Kind,
+        color: iced::Color,
     }
 
-    pub fn tooltip(color: iced::Color) -> Self {
-        Self {
-            kind: Kind::Tooltip,
-            color,
-        }
+    #[derive(Debug, Clone, Copy)]
+    enum Kind {
+        Line,
+        Tooltip,
+
+        Cell { selected: bool, focused: bool, highlight: Option<iced::Color> },
     }
 
-    fn lighten_color(&self) -> iced::Color {
-        let linear = self.color.into_linear();
+    impl Style {
+        pub fn cell(color: iced::Color) -> Self {
+            Self {
+                kind: Kind::Cell { selected: false, focused: false, highlight: None },
+                color,
+            }
+        }
 
-        let mut hsl: colorsys::Hsl =
-            colorsys::Rgb::from(crate::utils::color_scale_up(linear)).into();
+        /// Fills the background of a cell which isn't selected with `highlight`.
+        ///
+        pub fn highlighted(self, highlight: iced::Color) -> Self {
+            match self.kind {
+                Kind::Cell { selected, focused, .. } =>
+                    Self {
+                        kind: Kind::Cell { selected, focused, highlight: Some(highlight) },
 
-        let l = hsl.lightness();
-        hsl.set_lightness(l * color::LIGHTEN_PERCENT);
+                        ..self
+                    },
 
-        let s = hsl.saturation();
-        hsl.set_saturation(s * color::DESATURATE_PERCENT);
+                _ => self,
+            }
+        }
+
+        pub fn tooltip(color: iced::Color) -> Self {
+            Self {
+                kind: Kind::Tooltip,
+                color,
+            }
+        }
+
+        fn lighten_color(&self) -> iced::Color {
+            let linear = self.color.into_linear();
 
-        let linear = crate::utils::color_scale_down(colorsys::Rgb::from(hsl).into());
+            let mut hsl: colorsys::Hsl =
+                colorsys::Rgb::from(crate::utils::color_scale_up(linear)).into();
 
-        linear.into()
+            let l = hsl.lightness();
+            hsl.set_lightness(l * color::LIGHTEN_PERCENT);
+
+            let s = hsl.saturation();
+            hsl.set_saturation(s * color::DESATURATE_PERCENT);
+
+            let linear = crate::utils::color_scale_down(colorsys::Rgb::from(hsl).into());
+
+            linear.into()
+        }
     }
-}
 
-impl container::StyleSheet for Style {
-    fn style(&self) -> container::Style {
-        match self.kind {
-            Kind::Cell { selected } =>
-                container::Style {
-                    border_color: self.color,
-                    border_width: cell::WIDTH,
-                    border_radius: cell::RADIUS,
-
-                    background: Some({
-                        if selected {
-                            color::SELECTED.into()
-
-                        } else if self.color == iced::Color::BLACK {
-                            iced::Color::WHITE.into()
-
-                        } else {
-                            self.lighten_color().into()
-                        }
-                    }),
-
-                    ..fill![]
-                },
-
-            Kind::Tooltip =>
-                container::Style {
-                    border_width: cell::WIDTH,
-                    border_radius: cell::RADIUS,
-                    border_color: iced::Color::WHITE,
-
-                    background: Some({
-                        if self.color == iced::Color::BLACK {
-                            iced::Color::WHITE.into()
-
-                        } else {
-                            self.lighten_color().into()
-                        }
-                    }),
-
-                    ..fill![]
-                },
-
-            Kind::Line =>
-                container::Style {
-                    background: Some(self.color.into()),
-
-                    ..fill![]
-                },
+    impl container::StyleSheet for Style {
+        fn style(&self) -> container::Style {
+            match self.kind {
+                Kind::Cell { selected, focused, highlight } =>
+                    container::Style {
+                        border_color: if focused { color::FOCUSED } else { self.color },
+                        border_width: cell::WIDTH,
+                        border_radius: cell::RADIUS,
+
+                        background: Some({
+                            if selected {
+                                color::SELECTED.into()
+
+                            } else if let Some(highlight) = highlight {
+                                highlight.into()
+
+                            } else if self.color == iced::Color::BLACK {
+                                iced::Color::WHITE.into()
+
+                            } else {
+                                self.lighten_color().into()
+                            }
+                        }),
+
+                        ..fill![]
+                    },
+
+                Kind::Tooltip =>
+                    container::Style {
+                        border_width: cell::WIDTH,
+                        border_radius: cell::RADIUS,
+                        border_color: iced::Color::WHITE,
+
+                        background: Some({
+                            if self.color == iced::Color::BLACK {
+                                iced::Color::WHITE.into()
+
+                            } else {
+                                self.lighten_color().into()
+                            }
+                        }),
+
+                        ..fill![]
+                    },
+
+                Kind::Line =>
+                    container::Style {
+                        background: Some(self.color.into()),
+
+                        ..fill![]
+                    },
+            }
         }
     }
-}
 
-impl container::StyleSheet for Tooltip {
-    fn style(&self) -> container::Style {
-        container::Style {
-            border_color: iced::Color::BLACK,
-            border_width: cell::WIDTH / 3.,
-            border_radius: cell::RADIUS,
+    impl container::StyleSheet for Tooltip {
+        fn style(&self) -> container::Style {
+            container::Style {
+                border_color: iced::Color::BLACK,
+                border_width: cell::WIDTH / 3.,
+                border_radius: cell::RADIUS,
 
-            background: Some(color![204, 235, 238, 0.7].into()),
+                background: Some(color![204, 235, 238, 0.7].into()),
 
-            ..fill![]
+                ..fill![]
+            }
         }
     }
-}
 
-impl container::StyleSheet for PopUp {
-    fn style(&self) -> container::Style {
-        container::Style {
-            background: Some(color![0, 121, 199].into()),
+    impl container::StyleSheet for PopUp {
+        fn style(&self) -> container::Style {
+            container::Style {
+                background: Some(color![0, 121, 199].into()),
 
-            ..fill![]
+                ..fill![]
+            }
         }
     }
-}
 
-impl container::StyleSheet for Error {
-    fn style(&self) -> container::Style {
-        container::Style {
-            background: Some(color![248, 73, 88].into()),
-            text_color: Some(color![244, 244, 244].into()),
+    impl container::StyleSheet for Error {
+        fn style(&self) -> container::Style {
+            container::Style {
+                background: Some(color![248, 73, 88].into()),
+                text_color: Some(color![244, 244, 244].into()),
 
-            ..fill![]
+                ..fill![]
+            }
         }
     }
-}
 
-impl iced::button::StyleSheet for Style {
-    fn active(&self) -> iced::button::Style {
-        match self.kind {
-            Kind::Cell { selected } =>
-                iced::button::Style {
-                    shadow_offset: [0., 0.].into(),
-                    background: Some({
-                        if selected {
-                            color::SELECTED.into()
-
-                        } else if self.color == iced::Color::BLACK {
-                            iced::Color::WHITE.into()
-
-                        } else {
-                            self.lighten_color().into()
-                        }
-                    }),
-
-                    border_radius: cell::RADIUS,
-                    border_width: cell::WIDTH,
-                    border_color: self.color,
-
-                    text_color: self.color,
-                },
-
-            Kind::Tooltip =>
-                iced::button::Style {
-                    shadow_offset: [0., 0.].into(),
-                    background: Some({
-                        if self.color == iced::Color::BLACK {
-                            iced::Color::WHITE.into()
-
-                        } else {
-                            self.lighten_color().into()
-                        }
-                    }),
-
-                    border_color: iced::Color::WHITE,
-                    border_radius: cell::RADIUS,
-                    border_width: cell::WIDTH,
-
-                    text_color: self.color,
-                },
-
-            Kind::Line =>
-                iced::button::Style {
-                    background: Some(self.color.into()),
-
-                    ..fill![]
-                },
+    impl iced::button::StyleSheet for Style {
+        fn active(&self) -> iced::button::Style {
+            match self.kind {
+                Kind::Cell { selected, focused, highlight } =>
+                    iced::button::Style {
+                        shadow_offset: [0., 0.].into(),
+                        background: Some({
+                            if selected {
+                                color::SELECTED.into()
+
+                            } else if let Some(highlight) = highlight {
+                                highlight.into()
+
+                            } else if self.color == iced::Color::BLACK {
+                                iced::Color::WHITE.into()
+
+                            } else {
+                                self.lighten_color().into()
+                            }
+                        }),
+
+                        border_radius: cell::RADIUS,
+                        border_width: cell::WIDTH,
+                        border_color: if focused { color::FOCUSED } else { self.color },
+
+                        text_color: self.color,
+                    },
+
+                Kind::Tooltip =>
+                    iced::button::Style {
+                        shadow_offset: [0., 0.].into(),
+                        background: Some({
+                            if self.color == iced::Color::BLACK {
+                                iced::Color::WHITE.into()
+
+                            } else {
+                                self.lighten_color().into()
+                            }
+                        }),
+
+                        border_color: iced::Color::WHITE,
+                        border_radius: cell::RADIUS,
+                        border_width: cell::WIDTH,
+
+                        text_color: self.color,
+                    },
+
+                Kind::Line =>
+                    iced::button::Style {
+                        background: Some(self.color.into()),
+
+                        ..fill![]
+                    },
+            }
         }
-    }
 
-    fn hovered(&self) -> iced::button::Style {
-        let mut style = self.active();
+        fn hovered(&self) -> iced::button::Style {
+            let mut style = self.active();
 
-        style.shadow_offset = [0., 1.].into();
+            style.shadow_offset = [0., 1.].into();
 
-        style
+            style
+        }
     }
 }
@@ -0,0 +1,59 @@
+use std::sync::OnceLock;
+
+
+
+/// Font of the cell labels, the same as `text::fonts::LABEL`.
+///
+pub const FONT: &[u8] = include_bytes![ "../../res/fonts/DejaVuSans.ttf" ];
+
+/// Size of the cell labels at the natural scale.
+///
+pub const LABEL_SIZE: u16 = 20;
+
+
+
+/// Width of `text` set in the label font at `size`, from the advances of its glyphs.
+///
+/// Characters missing from the font count with the width of its replacement glyph, since that's
+/// what gets drawn in their place.
+///
+pub fn text_width(text: &str, size: u16) -> f32 {
+    let face = face();
+
+    let units: u32 =
+    text.chars()
+        .map(|c| face.glyph_index(c).unwrap_or(ttf_parser::GlyphId(0)))
+        .map(|glyph| face.glyph_hor_advance(glyph).unwrap_or(0) as u32)
+        .sum();
+
+    units as f32 * size as f32 / face.units_per_em() as f32
+}
+
+/// The label font, parsed on first use.
+///
+fn face() -> &'static ttf_parser::Face<'static> {
+    static FACE: OnceLock<ttf_parser::Face<'static>> = OnceLock::new();
+
+    FACE.get_or_init(|| ttf_parser::Face::parse(FONT, 0).expect("the bundled font is valid"))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greek_letters_are_measured_by_their_glyphs() {
+        let face = face();
+
+        for c in "αβγδεζηθικλμνξοπρστυφχψωΓΔΘΛΞΠΣΦΨΩ".chars() {
+            assert![face.glyph_index(c).is_some(), "no glyph for {}", c];
+        }
+
+        let notdef = face.glyph_hor_advance(ttf_parser::GlyphId(0)).unwrap() as f32 * LABEL_SIZE as f32 / face.units_per_em() as f32;
+
+        assert![text_width("α", LABEL_SIZE) > 0.0];
+        assert_ne![text_width("α", LABEL_SIZE), notdef];
+    }
+}
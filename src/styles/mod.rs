@@ -1,2 +1,4 @@
+#[cfg(feature = "gui")]
 pub mod text;
 pub mod container;
+pub mod metrics;
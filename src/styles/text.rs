@@ -13,6 +13,10 @@ pub mod fonts {
 
     font! { BOLD = Bold: "../../res/fonts/GillSans-SemiBold.ttf" }
     font! { REGULAR = Regular: "../../res/fonts/GillSans-Regular.ttf" }
+
+    // NOTE: Gill Sans has no Greek letters, which the default labels are made of.
+    //
+    font! { LABEL = Label: "../../res/fonts/DejaVuSans.ttf" }
 }
 
 
@@ -56,6 +56,12 @@ macro_rules! color {
     };
 }
 
+/// Width of `text` as displayed in a cell, at the natural scale.
+///
+pub fn text_width(text: &str) -> u16 {
+    crate::styles::metrics::text_width(text, crate::styles::metrics::LABEL_SIZE).ceil() as u16
+}
+
 pub fn color_scale_up(color: [f32; 4]) -> [f32; 4] {
     let [r, g, b, a] = color;
 
@@ -102,6 +108,7 @@ pub trait OrErr {
     fn or_err<E>(&self, e: impl FnOnce() -> E) -> Result<(), E>;
 }
 pub trait ThenOk {
+    #[allow(clippy::result_unit_err)]
     fn then_ok<O>(&self, o: impl FnOnce() -> O) -> Result<O, ()>;
 }
 